@@ -0,0 +1,79 @@
+use crate::sim_control::simulated_delta;
+use crate::{Particle, ParticleCount, Pipeline};
+use bevy::prelude::*;
+use bevy_easings::EasingComponent;
+use bevy_rapier2d::prelude::*;
+
+/// Fraction of a lifetime over which an expiring particle fades out.
+const FADE_FRACTION: f32 = 0.3;
+
+/// Simulated seconds since the particle was spawned.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct Age(pub f32);
+
+/// Simulated seconds after which a temporary particle fades out and is
+/// despawned.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Lifetime(pub f32);
+
+impl Lifetime {
+    /// Opacity at `age`, fading from 1 to 0 over the end of the lifetime.
+    pub fn opacity(&self, age: Age) -> f32 {
+        let fade = self.0 * FADE_FRACTION;
+        ((self.0 - age.0) / fade).clamp(0.0, 1.0)
+    }
+
+    pub fn expired(&self, age: Age) -> bool {
+        age.0 >= self.0
+    }
+}
+
+fn advance_age(
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut ages: Query<&mut Age>,
+) {
+    let delta = simulated_delta(&time, &rapier_config);
+    if delta == 0.0 {
+        return;
+    }
+    for mut age in ages.iter_mut() {
+        age.0 += delta;
+    }
+}
+
+/// Fades particles towards the end of their lifetime and despawns them once
+/// it is over.
+#[allow(clippy::type_complexity)]
+fn expire_particles(
+    mut commands: Commands,
+    mut particle_counter: ResMut<ParticleCount>,
+    mut particles: Query<(Entity, &Age, &Lifetime, &mut Sprite, Option<&Particle>), Changed<Age>>,
+) {
+    for (entity, age, lifetime, mut sprite, particle) in particles.iter_mut() {
+        if lifetime.expired(*age) {
+            commands.entity(entity).despawn_recursive();
+            if particle.is_some() {
+                particle_counter.0 = particle_counter.0.saturating_sub(1);
+            }
+            continue;
+        }
+        let opacity = lifetime.opacity(*age);
+        if opacity < 1.0 {
+            // The pulsing easing would overwrite the color every frame.
+            commands.entity(entity).remove::<EasingComponent<Sprite>>();
+            sprite.color.set_a(opacity);
+        }
+    }
+}
+
+/// Ages particles in simulated time and removes those whose lifetime ran
+/// out.
+pub struct AgePlugin;
+
+impl Plugin for AgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(advance_age.after(Pipeline::Spawning))
+            .add_system(expire_particles.after(advance_age));
+    }
+}
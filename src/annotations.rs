@@ -0,0 +1,221 @@
+use crate::camera::{cursor_world_position, MainCamera};
+use crate::console::{ConsoleLog, LogCategory};
+use crate::input::{Action, ActionInput};
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::sim_control::{SimClock, FIXED_TIMESTEP};
+use crate::tools::{pointer_over_ui, Tool};
+use crate::units::format_duration;
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+
+/// Drags shorter than this on screen, in logical pixels, place a note
+/// without an arrow.
+const MIN_ARROW_PIXELS: f32 = 8.0;
+
+const NOTE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 220, 120);
+
+/// A text note in world space, saved with the scene.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Annotation {
+    pub text: String,
+    pub position: Vec2,
+    /// Where the arrow from the text points to, if any.
+    pub arrow: Option<Vec2>,
+    /// Simulated seconds at which the note was placed.
+    pub time: f32,
+}
+
+#[derive(Resource)]
+pub struct Annotations {
+    pub notes: Vec<Annotation>,
+    pub visible: bool,
+    pub show_times: bool,
+    /// Text of the next note placed with the annotate tool.
+    pub draft: String,
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Self {
+            notes: Vec::new(),
+            visible: true,
+            show_times: true,
+            draft: String::new(),
+        }
+    }
+}
+
+/// Where the annotate tool was pressed, while the button is held.
+#[derive(Resource, Default)]
+struct AnnotationDrag {
+    start: Option<Vec2>,
+    end: Option<Vec2>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn annotate_tool(
+    tool: Res<Tool>,
+    actions: ActionInput,
+    clock: Res<SimClock>,
+    windows: Res<Windows>,
+    mut egui_context: ResMut<EguiContext>,
+    mut drag: ResMut<AnnotationDrag>,
+    mut annotations: ResMut<Annotations>,
+    mut log: ResMut<ConsoleLog>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    if *tool != Tool::Annotate {
+        if drag.start.is_some() {
+            *drag = default();
+        }
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    if let Some(cursor) = cursor_world_position(window, camera, camera_transform) {
+        if actions.just_pressed(Action::UseTool) && !pointer_over_ui(&mut egui_context) {
+            drag.start = Some(cursor);
+        }
+        if drag.start.is_some() && actions.pressed(Action::UseTool) {
+            drag.end = Some(cursor);
+        }
+    }
+    if !actions.just_released(Action::UseTool) {
+        return;
+    }
+    let (Some(start), Some(end)) = (drag.start.take(), drag.end.take()) else {
+        return;
+    };
+
+    let arrow = match (
+        world_to_screen(camera, camera_transform, window, start),
+        world_to_screen(camera, camera_transform, window, end),
+    ) {
+        (Some(a), Some(b)) if a.distance(b) >= MIN_ARROW_PIXELS => Some(end),
+        _ => None,
+    };
+    let text = match annotations.draft.trim() {
+        "" => "Note".to_string(),
+        text => text.to_string(),
+    };
+    log.category(LogCategory::Edit)
+        .info(format!("Added annotation \"{text}\""));
+    annotations.notes.push(Annotation {
+        text,
+        position: start,
+        arrow,
+        time: clock.steps as f32 * FIXED_TIMESTEP,
+    });
+}
+
+fn draw_annotations(
+    annotations: Res<Annotations>,
+    drag: Res<AnnotationDrag>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    if !annotations.visible {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let to_screen = |point: Vec2| world_to_screen(camera, camera_transform, window, point);
+    let painter = overlay_painter(egui_context.ctx_mut(), "annotations", camera);
+    let stroke = egui::Stroke::new(1.5, NOTE_COLOR);
+
+    for note in &annotations.notes {
+        let Some(anchor) = to_screen(note.position) else {
+            continue;
+        };
+        if let Some(head) = note.arrow.and_then(to_screen) {
+            painter.arrow(anchor, head - anchor, stroke);
+        }
+        painter.text(
+            anchor + egui::vec2(0.0, -4.0),
+            egui::Align2::CENTER_BOTTOM,
+            &note.text,
+            egui::FontId::proportional(14.0),
+            NOTE_COLOR,
+        );
+        if annotations.show_times {
+            painter.text(
+                anchor + egui::vec2(0.0, 4.0),
+                egui::Align2::CENTER_TOP,
+                format!("t = {}", format_duration(note.time)),
+                egui::FontId::monospace(10.0),
+                egui::Color32::from_white_alpha(120),
+            );
+        }
+    }
+
+    if let (Some(start), Some(end)) = (drag.start, drag.end) {
+        if let (Some(a), Some(b)) = (to_screen(start), to_screen(end)) {
+            if a.distance(b) >= MIN_ARROW_PIXELS {
+                painter.arrow(a, b - a, egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE));
+            }
+        }
+    }
+}
+
+fn annotations_ui(
+    tool: Res<Tool>,
+    mut egui_context: ResMut<EguiContext>,
+    mut annotations: ResMut<Annotations>,
+) {
+    egui::Window::new("Annotations")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Text");
+                ui.text_edit_singleline(&mut annotations.draft);
+            });
+            if *tool == Tool::Annotate {
+                ui.label("Click to place a note, drag to point an arrow.");
+            }
+            ui.checkbox(&mut annotations.visible, "Show annotations");
+            ui.checkbox(&mut annotations.show_times, "Show timestamps");
+            ui.separator();
+
+            let mut removed = None;
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for (index, note) in annotations.notes.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.monospace(format_duration(note.time));
+                            ui.text_edit_singleline(&mut note.text);
+                            if ui.small_button("x").clicked() {
+                                removed = Some(index);
+                            }
+                        });
+                    }
+                });
+            if let Some(index) = removed {
+                annotations.notes.remove(index);
+            }
+            if !annotations.notes.is_empty() && ui.button("Clear all").clicked() {
+                annotations.notes.clear();
+            }
+        });
+}
+
+/// Tool placing text notes and arrows in the arena, to label regions and
+/// events for screenshots and recordings.
+pub struct AnnotationsPlugin;
+
+impl Plugin for AnnotationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Annotations>()
+            .init_resource::<AnnotationDrag>()
+            .add_system(annotate_tool.label(Pipeline::Input))
+            .add_system(
+                draw_annotations
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            )
+            .add_system(annotations_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
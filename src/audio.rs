@@ -0,0 +1,168 @@
+use crate::impacts::Impact;
+use crate::tools::pointer_over_ui;
+use crate::units::from_millimeters;
+use crate::Pipeline;
+use bevy::audio::AudioSource;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::sync::Arc;
+
+const SAMPLE_RATE: u32 = 44_100;
+/// At most this many impacts are played per frame, the strongest first, so
+/// a collapsing pile does not turn into noise.
+const MAX_IMPACTS_PER_FRAME: usize = 4;
+/// Impacts below this energy in joules are silent.
+const MIN_IMPACT_ENERGY: f32 = 1e-6;
+/// Impact energy in joules played at full volume.
+const FULL_VOLUME_ENERGY: f32 = 1e-3;
+/// Particle size in millimeters played at the recorded pitch. Smaller
+/// particles sound higher, larger ones lower.
+const REFERENCE_SIZE_MM: f32 = 4.0;
+
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct AudioSettings {
+    pub enabled: bool,
+    pub volume: f32,
+    pub collisions: bool,
+    pub ui_clicks: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.5,
+            collisions: true,
+            ui_clicks: true,
+        }
+    }
+}
+
+/// Sounds synthesized at startup, so no audio files have to be shipped.
+#[derive(Resource)]
+struct Sounds {
+    impact: Handle<AudioSource>,
+    click: Handle<AudioSource>,
+}
+
+/// Encodes mono samples in -1..1 as a 16-bit PCM WAV file.
+fn wav(samples: impl ExactSizeIterator<Item = f32>) -> AudioSource {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel.
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    AudioSource {
+        bytes: Arc::from(bytes),
+    }
+}
+
+/// A sine tone with its first overtone that dies away exponentially.
+fn decaying_tone(frequency: f32, seconds: f32) -> AudioSource {
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    wav((0..count).map(|i| {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        let envelope = (-t * 6.0 / seconds).exp();
+        let tone = (TAU * frequency * t).sin() + 0.3 * (TAU * 2.7 * frequency * t).sin();
+        0.7 * envelope * tone
+    }))
+}
+
+fn create_sounds(mut sources: ResMut<Assets<AudioSource>>, mut commands: Commands) {
+    commands.insert_resource(Sounds {
+        impact: sources.add(decaying_tone(660.0, 0.08)),
+        click: sources.add(decaying_tone(1800.0, 0.02)),
+    });
+}
+
+fn play_impacts(
+    settings: Res<AudioSettings>,
+    sounds: Res<Sounds>,
+    audio: Res<Audio>,
+    mut impacts: EventReader<Impact>,
+) {
+    if !settings.enabled || !settings.collisions {
+        impacts.clear();
+        return;
+    }
+    let mut impacts: Vec<&Impact> = impacts
+        .iter()
+        .filter(|impact| impact.energy >= MIN_IMPACT_ENERGY)
+        .collect();
+    impacts.sort_by(|a, b| b.energy.total_cmp(&a.energy));
+    for impact in impacts.into_iter().take(MAX_IMPACTS_PER_FRAME) {
+        let loudness = (impact.energy / FULL_VOLUME_ENERGY).sqrt().min(1.0);
+        let pitch = (from_millimeters(REFERENCE_SIZE_MM) / impact.size)
+            .sqrt()
+            .clamp(0.5, 2.0);
+        audio.play_with_settings(
+            sounds.impact.clone(),
+            PlaybackSettings::ONCE
+                .with_volume(settings.volume * loudness)
+                .with_speed(pitch),
+        );
+    }
+}
+
+fn play_ui_clicks(
+    settings: Res<AudioSettings>,
+    sounds: Res<Sounds>,
+    audio: Res<Audio>,
+    buttons: Res<Input<MouseButton>>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if settings.enabled
+        && settings.ui_clicks
+        && buttons.just_pressed(MouseButton::Left)
+        && pointer_over_ui(&mut egui_context)
+    {
+        audio.play_with_settings(
+            sounds.click.clone(),
+            PlaybackSettings::ONCE.with_volume(settings.volume * 0.5),
+        );
+    }
+}
+
+fn audio_ui(mut egui_context: ResMut<EguiContext>, mut settings: ResMut<AudioSettings>) {
+    egui::Window::new("Audio")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.checkbox(&mut settings.enabled, "Sound");
+            ui.add_enabled_ui(settings.enabled, |ui| {
+                ui.add(egui::Slider::new(&mut settings.volume, 0.0..=1.0).text("Volume"));
+                ui.checkbox(&mut settings.collisions, "Collisions");
+                ui.checkbox(&mut settings.ui_clicks, "Clicks");
+            });
+        });
+}
+
+/// Collision and UI click sounds.
+pub struct AudioFeedbackPlugin;
+
+impl Plugin for AudioFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioSettings>()
+            .register_type::<AudioSettings>()
+            .add_startup_system(create_sounds)
+            .add_system(play_impacts)
+            .add_system(play_ui_clicks.label(Pipeline::Input))
+            .add_system(audio_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
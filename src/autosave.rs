@@ -0,0 +1,137 @@
+use crate::console::{ConsoleLog, LogCategory};
+use crate::scene::SceneCommand;
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    /// Seconds between autosaves.
+    pub interval: f32,
+    /// Number of autosave files kept; older ones are deleted.
+    pub retention: usize,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 60.0,
+            retention: 5,
+        }
+    }
+}
+
+/// Latest autosave found at startup, offered for restoring until the user
+/// decides.
+#[derive(Resource, Default)]
+struct RecoveryOffer(Option<PathBuf>);
+
+fn autosave_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("physicsboi").join("autosave"))
+}
+
+/// Autosave files, oldest first. Names embed a zero-padded timestamp so
+/// lexicographic order is chronological.
+fn autosave_files() -> Vec<PathBuf> {
+    let Some(dir) = autosave_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("autosave-") && name.ends_with(".ron"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn find_recovery(mut offer: ResMut<RecoveryOffer>) {
+    offer.0 = autosave_files().pop();
+}
+
+fn autosave(
+    time: Res<Time>,
+    settings: Res<AutosaveSettings>,
+    mut last_save: Local<f32>,
+    mut log: ResMut<ConsoleLog>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    let now = time.elapsed_seconds();
+    if !settings.enabled || now - *last_save < settings.interval {
+        return;
+    }
+    *last_save = now;
+    let Some(dir) = autosave_dir() else {
+        return;
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log.category(LogCategory::Scene)
+            .warn(format!("Could not create {}: {e}", dir.display()));
+        return;
+    }
+
+    let files = autosave_files();
+    let keep = settings.retention.saturating_sub(1);
+    for old in &files[..files.len().saturating_sub(keep)] {
+        let _ = std::fs::remove_file(old);
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    scene_commands.send(SceneCommand::Save(
+        dir.join(format!("autosave-{timestamp:012}.ron")),
+    ));
+}
+
+fn recovery_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut offer: ResMut<RecoveryOffer>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    let Some(path) = offer.0.clone() else {
+        return;
+    };
+    egui::Window::new("Restore autosave?")
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(format!(
+                "Found an autosave from the last session:\n{}",
+                path.display()
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Restore").clicked() {
+                    scene_commands.send(SceneCommand::Load(path.clone()));
+                    offer.0 = None;
+                }
+                if ui.button("Discard").clicked() {
+                    offer.0 = None;
+                }
+            });
+        });
+}
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutosaveSettings>()
+            .register_type::<AutosaveSettings>()
+            .init_resource::<RecoveryOffer>()
+            .add_startup_system(find_recovery)
+            .add_system(autosave)
+            .add_system(recovery_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
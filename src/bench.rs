@@ -0,0 +1,209 @@
+use crate::cli::Cli;
+use crate::headless::headless_app;
+use crate::units::from_millimeters;
+use crate::{ParticleBuilder, ParticleCount};
+use bevy::app::AppExit;
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics, DiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy::utils::Instant;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Particle counts benchmarked one after another.
+const LOADS: [usize; 3] = [1_000, 5_000, 10_000];
+/// Frames run before measuring, so spawning does not skew the averages.
+const WARMUP_FRAMES: u64 = 10;
+const PARTICLE_SIZE_MM: f32 = 3.0;
+const PARTICLE_SPACING_MM: f32 = 4.0;
+/// Inside of the default arena in millimeters, where the particles are
+/// stacked.
+const ARENA_MIN_MM: Vec2 = Vec2::new(-198.0, -248.0);
+const ARENA_WIDTH_MM: f32 = 396.0;
+
+const FRAME_TIME: DiagnosticId = DiagnosticId::from_u128(0x5d1b7a2e4c0f4e8a9b3c6d7e8f901234);
+const UPDATE_TIME: DiagnosticId = DiagnosticId::from_u128(0x5d1b7a2e4c0f4e8a9b3c6d7e8f901235);
+const PHYSICS_TIME: DiagnosticId = DiagnosticId::from_u128(0x5d1b7a2e4c0f4e8a9b3c6d7e8f901236);
+const POST_UPDATE_TIME: DiagnosticId = DiagnosticId::from_u128(0x5d1b7a2e4c0f4e8a9b3c6d7e8f901237);
+
+/// Diagnostics reported per load, in table order.
+const REPORTED: [(DiagnosticId, &str); 4] = [
+    (FRAME_TIME, "frame_ms"),
+    (UPDATE_TIME, "update_ms"),
+    (PHYSICS_TIME, "physics_ms"),
+    (POST_UPDATE_TIME, "post_update_ms"),
+];
+
+/// Stages inserted around rapier's, whose boundaries are timed.
+const UPDATE_END: &str = "bench_update_end";
+const PHYSICS_END: &str = "bench_physics_end";
+
+#[derive(Resource)]
+struct BenchLoad {
+    particles: usize,
+    steps: u64,
+    /// Filled with the averages of [`REPORTED`] when the run ends, since the
+    /// app is gone once `run` returns.
+    averages: Arc<Mutex<Vec<f64>>>,
+}
+
+#[derive(Resource, Default)]
+struct StageClock {
+    frames: u64,
+    frame_start: Option<Instant>,
+    last_mark: Option<Instant>,
+}
+
+impl StageClock {
+    fn measuring(&self) -> bool {
+        self.frames > WARMUP_FRAMES
+    }
+
+    /// Milliseconds since the previous mark.
+    fn lap(&mut self) -> f64 {
+        let now = Instant::now();
+        let elapsed = self
+            .last_mark
+            .map_or(0.0, |last| (now - last).as_secs_f64());
+        self.last_mark = Some(now);
+        elapsed * 1000.0
+    }
+}
+
+/// Stacks the particles in rows from the floor of the arena up.
+fn spawn_load(
+    mut commands: Commands,
+    load: Res<BenchLoad>,
+    mut particle_counter: ResMut<ParticleCount>,
+) {
+    let columns = (ARENA_WIDTH_MM / PARTICLE_SPACING_MM) as usize;
+    for i in 0..load.particles {
+        let offset = Vec2::new((i % columns) as f32, (i / columns) as f32) * PARTICLE_SPACING_MM;
+        let position_mm = ARENA_MIN_MM + offset + PARTICLE_SPACING_MM / 2.0;
+        let particle = ParticleBuilder::at(position_mm * from_millimeters(1.0))
+            .size(from_millimeters(PARTICLE_SIZE_MM))
+            .build()
+            .expect("benchmark particles are valid");
+        commands.spawn(particle);
+        particle_counter.0 += 1;
+    }
+}
+
+fn register_diagnostics(load: Res<BenchLoad>, mut diagnostics: ResMut<Diagnostics>) {
+    for (id, name) in REPORTED {
+        diagnostics.add(Diagnostic::new(id, name, load.steps as usize).with_suffix("ms"));
+    }
+}
+
+fn start_frame(mut clock: ResMut<StageClock>) {
+    clock.frames += 1;
+    clock.frame_start = Some(Instant::now());
+    clock.last_mark = clock.frame_start;
+}
+
+fn mark_update(mut clock: ResMut<StageClock>, mut diagnostics: ResMut<Diagnostics>) {
+    let elapsed = clock.lap();
+    if clock.measuring() {
+        diagnostics.add_measurement(UPDATE_TIME, || elapsed);
+    }
+}
+
+fn mark_physics(mut clock: ResMut<StageClock>, mut diagnostics: ResMut<Diagnostics>) {
+    let elapsed = clock.lap();
+    if clock.measuring() {
+        diagnostics.add_measurement(PHYSICS_TIME, || elapsed);
+    }
+}
+
+fn finish_frame(
+    load: Res<BenchLoad>,
+    mut clock: ResMut<StageClock>,
+    mut diagnostics: ResMut<Diagnostics>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let elapsed = clock.lap();
+    if clock.measuring() {
+        let frame = clock
+            .frame_start
+            .map_or(0.0, |start| start.elapsed().as_secs_f64() * 1000.0);
+        diagnostics.add_measurement(POST_UPDATE_TIME, || elapsed);
+        diagnostics.add_measurement(FRAME_TIME, || frame);
+    }
+    if clock.frames >= WARMUP_FRAMES + load.steps {
+        *load.averages.lock().unwrap() = REPORTED
+            .iter()
+            .map(|(id, _)| {
+                diagnostics
+                    .get(*id)
+                    .and_then(Diagnostic::average)
+                    .unwrap_or(f64::NAN)
+            })
+            .collect();
+        exit.send(AppExit);
+    }
+}
+
+/// Runs one load and returns the averages of [`REPORTED`].
+fn run_load(cli: &Cli, particles: usize) -> Vec<f64> {
+    let averages = Arc::new(Mutex::new(Vec::new()));
+    headless_app(cli)
+        .add_plugin(DiagnosticsPlugin)
+        .insert_resource(BenchLoad {
+            particles,
+            steps: cli.bench_steps,
+            averages: averages.clone(),
+        })
+        .init_resource::<StageClock>()
+        .add_startup_system(spawn_load)
+        .add_startup_system(register_diagnostics)
+        // Added after the physics plugin, so these end up directly after
+        // Update and directly before PostUpdate, around rapier's stages.
+        .add_stage_after(
+            CoreStage::Update,
+            UPDATE_END,
+            SystemStage::single(mark_update),
+        )
+        .add_stage_before(
+            CoreStage::PostUpdate,
+            PHYSICS_END,
+            SystemStage::single(mark_physics),
+        )
+        .add_system_to_stage(CoreStage::First, start_frame)
+        .add_system_to_stage(CoreStage::Last, finish_frame)
+        .run();
+    let averages = averages.lock().unwrap().clone();
+    averages
+}
+
+/// Benchmarks the headless simulation at increasing particle counts, prints
+/// the average time spent per frame and stage and writes them as CSV next
+/// to `--output`.
+pub fn run(cli: Cli) {
+    let mut csv = String::from("particles,steps");
+    for (_, name) in REPORTED {
+        let _ = write!(csv, ",{name}");
+    }
+    csv.push('\n');
+
+    println!(
+        "{:>10} {:>10} {:>10} {:>10} {:>14}",
+        "particles", "frame ms", "update ms", "physics ms", "post update ms"
+    );
+    for particles in LOADS {
+        let averages = run_load(&cli, particles);
+        println!(
+            "{:>10} {:>10.3} {:>10.3} {:>10.3} {:>14.3}",
+            particles, averages[0], averages[1], averages[2], averages[3]
+        );
+        let _ = write!(csv, "{particles},{}", cli.bench_steps);
+        for average in averages {
+            let _ = write!(csv, ",{average:.4}");
+        }
+        csv.push('\n');
+    }
+
+    let path = cli.output.with_extension("bench.csv");
+    match std::fs::write(&path, csv) {
+        Ok(()) => println!("Wrote {}", path.display()),
+        Err(e) => eprintln!("Could not write {}: {e}", path.display()),
+    }
+}
@@ -0,0 +1,268 @@
+use crate::input::{Action, ActionInput};
+use crate::selection::Selected;
+use crate::split_screen::ComparisonEntity;
+use crate::{Particle, Pipeline};
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::prelude::*;
+use bevy_easings::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The camera driven by user input and used for cursor picking.
+#[derive(Component)]
+pub struct MainCamera;
+
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct CameraSettings {
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Fractional change of the zoom level per scroll step.
+    pub zoom_speed: f32,
+    /// How quickly the camera catches up with a followed particle, per second.
+    pub follow_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            min_zoom: 0.1,
+            max_zoom: 10.0,
+            zoom_speed: 0.1,
+            follow_speed: 5.0,
+        }
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CameraMode {
+    /// Manual panning and zooming.
+    #[default]
+    Free,
+    /// Keep the first selected particle centered.
+    Follow,
+    /// Continuously frame the bounding box of all particles.
+    FitAll,
+    /// Fixed view of the arena.
+    Arena,
+}
+
+pub fn modifier_pressed(keys: &Input<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::LControl, KeyCode::RControl])
+}
+
+pub fn cursor_world_position(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .map(|ray| ray.origin.truncate())
+}
+
+fn pan_camera(
+    mode: Res<CameraMode>,
+    actions: ActionInput,
+    mut motion_events: EventReader<MouseMotion>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    let delta: Vec2 = motion_events.iter().map(|ev| ev.delta).sum();
+    if *mode != CameraMode::Free || !actions.pressed(Action::Pan) {
+        return;
+    }
+    let mut transform = camera_q.single_mut();
+    let scale = transform.scale.x;
+    transform.translation.x -= delta.x * scale;
+    transform.translation.y += delta.y * scale;
+}
+
+fn zoom_camera(
+    mode: Res<CameraMode>,
+    settings: Res<CameraSettings>,
+    keys: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    mut egui_context: ResMut<EguiContext>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    if !matches!(*mode, CameraMode::Free | CameraMode::Follow)
+        || modifier_pressed(&keys)
+        || egui_context.ctx_mut().is_pointer_over_area()
+    {
+        scroll_events.clear();
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let mut transform = camera_q.single_mut();
+
+    for ev in scroll_events.iter() {
+        let factor = if ev.y > 0.0 {
+            1.0 - settings.zoom_speed
+        } else {
+            1.0 + settings.zoom_speed
+        };
+        let old_scale = transform.scale.x;
+        let new_scale = (old_scale * factor).clamp(settings.min_zoom, settings.max_zoom);
+
+        // Keep the world point under the cursor fixed while zooming.
+        if *mode == CameraMode::Free {
+            if let Some(cursor) = window.cursor_position() {
+                let offset = cursor - Vec2::new(window.width(), window.height()) / 2.0;
+                let shift = offset * (old_scale - new_scale);
+                transform.translation += shift.extend(0.0);
+            }
+        }
+        transform.scale = Vec3::new(new_scale, new_scale, 1.0);
+    }
+}
+
+/// Zoom steps per second while a zoom action is held.
+const HELD_ZOOM_RATE: f32 = 10.0;
+
+/// Zooms around the view center while ZoomIn or ZoomOut is held.
+fn zoom_camera_held(
+    mode: Res<CameraMode>,
+    settings: Res<CameraSettings>,
+    time: Res<Time>,
+    actions: ActionInput,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    if !matches!(*mode, CameraMode::Free | CameraMode::Follow) {
+        return;
+    }
+    let direction =
+        actions.pressed(Action::ZoomOut) as i32 - actions.pressed(Action::ZoomIn) as i32;
+    if direction == 0 {
+        return;
+    }
+    let mut transform = camera_q.single_mut();
+    let factor =
+        (1.0 + settings.zoom_speed).powf(direction as f32 * HELD_ZOOM_RATE * time.delta_seconds());
+    let scale = (transform.scale.x * factor).clamp(settings.min_zoom, settings.max_zoom);
+    transform.scale = Vec3::new(scale, scale, 1.0);
+}
+
+fn follow_selected(
+    mode: Res<CameraMode>,
+    settings: Res<CameraSettings>,
+    time: Res<Time>,
+    selected: Query<&Transform, (With<Selected>, Without<MainCamera>)>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    if *mode != CameraMode::Follow {
+        return;
+    }
+    let Some(target) = selected.iter().next() else {
+        return;
+    };
+    let mut transform = camera_q.single_mut();
+    let t = (settings.follow_speed * time.delta_seconds()).min(1.0);
+    let position = transform
+        .translation
+        .truncate()
+        .lerp(target.translation.truncate(), t);
+    transform.translation = position.extend(transform.translation.z);
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn frame_camera(
+    mut commands: Commands,
+    mode: Res<CameraMode>,
+    settings: Res<CameraSettings>,
+    time: Res<Time>,
+    windows: Res<Windows>,
+    mut refresh: Local<Timer>,
+    particles: Query<(&Transform, &Particle), (Without<MainCamera>, Without<ComparisonEntity>)>,
+    camera_q: Query<(Entity, &Transform), With<MainCamera>>,
+) {
+    let (camera, transform) = camera_q.single();
+    let target = match *mode {
+        CameraMode::Free | CameraMode::Follow => {
+            if mode.is_changed() {
+                commands
+                    .entity(camera)
+                    .remove::<EasingComponent<Transform>>();
+            }
+            return;
+        }
+        CameraMode::Arena => {
+            if !mode.is_changed() {
+                return;
+            }
+            Transform::from_xyz(0.0, 0.0, transform.translation.z)
+        }
+        CameraMode::FitAll => {
+            refresh.tick(time.delta());
+            if !mode.is_changed() && !refresh.finished() {
+                return;
+            }
+            *refresh = Timer::from_seconds(0.5, TimerMode::Once);
+
+            let mut min = Vec2::splat(f32::MAX);
+            let mut max = Vec2::splat(f32::MIN);
+            for (particle_transform, particle) in particles.iter() {
+                let position = particle_transform.translation.truncate();
+                min = min.min(position - particle.size);
+                max = max.max(position + particle.size);
+            }
+            if min.x > max.x {
+                return;
+            }
+            let window = windows.get_primary().unwrap();
+            let size = (max - min) * 1.1;
+            let scale = (size.x / window.width())
+                .max(size.y / window.height())
+                .clamp(settings.min_zoom, settings.max_zoom);
+            Transform::from_translation(((min + max) / 2.0).extend(transform.translation.z))
+                .with_scale(Vec3::new(scale, scale, 1.0))
+        }
+    };
+
+    commands.entity(camera).insert(transform.ease_to(
+        target,
+        EaseFunction::QuadraticInOut,
+        EasingType::Once {
+            duration: Duration::from_millis(500),
+        },
+    ));
+}
+
+fn camera_mode_ui(mut egui_context: ResMut<EguiContext>, mut mode: ResMut<CameraMode>) {
+    egui::Window::new("Camera").show(egui_context.ctx_mut(), |ui| {
+        let mut selected = *mode;
+        ui.radio_value(&mut selected, CameraMode::Free, "Free");
+        ui.radio_value(&mut selected, CameraMode::Follow, "Follow selected");
+        ui.radio_value(&mut selected, CameraMode::FitAll, "Fit all particles");
+        ui.radio_value(&mut selected, CameraMode::Arena, "Arena");
+        if selected != *mode {
+            *mode = selected;
+        }
+    });
+}
+
+pub struct CameraControlsPlugin;
+
+impl Plugin for CameraControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraSettings>()
+            .init_resource::<CameraMode>()
+            .register_type::<CameraSettings>()
+            .add_system(camera_mode_ui.label(Pipeline::Ui).after(Pipeline::Visual))
+            .add_system(pan_camera.label(Pipeline::Input))
+            .add_system(zoom_camera.label(Pipeline::Input))
+            .add_system(zoom_camera_held.label(Pipeline::Input))
+            .add_system(
+                follow_selected
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            )
+            .add_system(
+                frame_camera
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            );
+    }
+}
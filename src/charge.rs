@@ -0,0 +1,146 @@
+use crate::camera::{cursor_world_position, MainCamera};
+use crate::console::{ConsoleLog, LogCategory};
+use crate::input::{Action, ActionInput};
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::placement::Placement;
+use crate::sizing::MIN_PARTICLE_SIZE;
+use crate::spawn_preview::translucent;
+use crate::tools::{pointer_over_ui, Tool};
+use crate::units::{format_length, from_millimeters};
+use crate::{ParticleBuilder, ParticleSpawner, Pipeline, PARTICLE_COLOR};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+
+/// Growth of the pending particle's edge length while the button is held, in
+/// millimeters per second.
+const GROWTH_RATE_MM: f32 = 20.0;
+/// Largest edge length a particle can be charged to, in millimeters.
+const MAX_SIZE_MM: f32 = 100.0;
+
+/// Where the button was pressed with the charge tool and for how long it has
+/// been held, in seconds.
+#[derive(Resource, Default)]
+struct Charge {
+    position: Option<Vec2>,
+    held: f32,
+}
+
+impl Charge {
+    /// Edge length of the pending particle in world units.
+    fn size(&self) -> f32 {
+        (MIN_PARTICLE_SIZE + from_millimeters(GROWTH_RATE_MM) * self.held)
+            .min(from_millimeters(MAX_SIZE_MM))
+    }
+}
+
+/// Grows a particle while the button is held and spawns it at rest where the
+/// button was pressed once released, at every placement position.
+#[allow(clippy::too_many_arguments)]
+fn charge_tool(
+    mut spawner: ParticleSpawner,
+    tool: Res<Tool>,
+    actions: ActionInput,
+    time: Res<Time>,
+    placement: Res<Placement>,
+    windows: Res<Windows>,
+    mut egui_context: ResMut<EguiContext>,
+    mut charge: ResMut<Charge>,
+    mut log: ResMut<ConsoleLog>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    if *tool != Tool::Charge {
+        if charge.position.is_some() {
+            *charge = default();
+        }
+        return;
+    }
+    if actions.just_pressed(Action::UseTool) && !pointer_over_ui(&mut egui_context) {
+        let window = windows.get_primary().unwrap();
+        let (camera, camera_transform) = camera_q.single();
+        charge.position = cursor_world_position(window, camera, camera_transform);
+        charge.held = 0.0;
+    }
+    if charge.position.is_some() && actions.pressed(Action::UseTool) {
+        charge.held += time.delta_seconds();
+    }
+    if !actions.just_released(Action::UseTool) {
+        return;
+    }
+    let Some(position) = charge.position.take() else {
+        return;
+    };
+
+    let size = charge.size();
+    let spawned = spawner
+        .place(
+            placement
+                .positions(position)
+                .into_iter()
+                .map(|position| ParticleBuilder::at(position).size(size)),
+        )
+        .len();
+    if spawned > 0 {
+        log.category(LogCategory::Edit).info(format!(
+            "Spawned {spawned} particle(s) of {}",
+            format_length(size)
+        ));
+    }
+}
+
+/// Shows the pending particle at its current size.
+fn draw_charge(
+    charge: Res<Charge>,
+    placement: Res<Placement>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    let Some(position) = charge.position else {
+        return;
+    };
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let painter = overlay_painter(egui_context.ctx_mut(), "charge", camera);
+    let size = charge.size();
+    let half = Vec2::splat(size / 2.0);
+    for position in placement.positions(position) {
+        let (Some(a), Some(b)) = (
+            world_to_screen(camera, camera_transform, window, position - half),
+            world_to_screen(camera, camera_transform, window, position + half),
+        ) else {
+            continue;
+        };
+        let rect = egui::Rect::from_two_pos(a, b);
+        painter.rect_filled(rect, 0.0, translucent(PARTICLE_COLOR, 0.3));
+        painter.rect_stroke(
+            rect,
+            0.0,
+            egui::Stroke::new(1.0, translucent(PARTICLE_COLOR, 0.8)),
+        );
+    }
+    if let Some(anchor) = world_to_screen(camera, camera_transform, window, position + half) {
+        painter.text(
+            anchor + egui::vec2(4.0, 0.0),
+            egui::Align2::LEFT_BOTTOM,
+            format_length(size),
+            egui::FontId::monospace(12.0),
+            egui::Color32::from_white_alpha(200),
+        );
+    }
+}
+
+/// Spawning one particle of a chosen size without a panel: hold the button
+/// to grow it, release to place it.
+pub struct ChargePlugin;
+
+impl Plugin for ChargePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Charge>()
+            .add_system(charge_tool.label(Pipeline::Input))
+            .add_system(
+                draw_charge
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            );
+    }
+}
@@ -0,0 +1,565 @@
+use crate::cli::Cli;
+use crate::console::{ConsoleLog, LogCategory};
+use crate::headless::ui_available;
+use crate::history::EditHistory;
+use crate::rewind::RewindBuffer;
+use crate::scene::SceneIo;
+use crate::sim_control::{simulated_delta, SimClock, SimulationControl};
+use crate::split_screen::ComparisonEntity;
+use crate::{spawn_wall, Particle, ParticleBuilder, ParticleCount, Pipeline, Wall};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Resource, Clone)]
+pub struct CheckpointSettings {
+    pub enabled: bool,
+    pub directory: PathBuf,
+    /// Simulated seconds between checkpoints.
+    pub interval: f32,
+    /// Every this many checkpoints is a full one, the others only store what
+    /// changed since.
+    pub full_every: u32,
+}
+
+impl Default for CheckpointSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: PathBuf::from("checkpoints"),
+            interval: 60.0,
+            full_every: 10,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+struct ParticleRecord {
+    position: Vec2,
+    /// Rotation around the z axis in radians.
+    rotation: f32,
+    linvel: Vec2,
+    angvel: f32,
+    size: f32,
+    pinned: bool,
+}
+
+/// Everything a full checkpoint restores besides the particles.
+#[derive(Serialize, Deserialize)]
+struct Arena {
+    gravity: Vec2,
+    time_scale: f32,
+    /// Position and half extents of every wall.
+    walls: Vec<(Vec2, Vec2)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    /// Physics steps taken when the checkpoint was written.
+    step: u64,
+    /// Step of the full checkpoint an incremental one builds on, the own
+    /// step for full ones.
+    base: u64,
+    /// Only stored in full checkpoints.
+    arena: Option<Arena>,
+    /// Every particle in full checkpoints, only those added or changed since
+    /// the full one in incremental checkpoints.
+    particles: Vec<(u64, ParticleRecord)>,
+    /// Particles of the full checkpoint that no longer exist.
+    removed: Vec<u64>,
+}
+
+impl Checkpoint {
+    fn file_name(&self) -> String {
+        if self.arena.is_some() {
+            format!("full-{:012}.ron", self.step)
+        } else {
+            format!("diff-{:012}.ron", self.step)
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// The particles of the last full checkpoint, which incremental ones are
+/// compared against.
+#[derive(Resource, Default)]
+struct CheckpointBase {
+    step: u64,
+    particles: HashMap<u64, ParticleRecord>,
+    /// Checkpoints written since the full one.
+    since_full: u32,
+    /// Forces the next checkpoint to be full, e.g. after a restore.
+    stale: bool,
+    /// Simulated seconds since the last checkpoint was written.
+    elapsed: f32,
+}
+
+/// Request to restore the latest checkpoint in a directory.
+pub struct RestoreCheckpoint(pub PathBuf);
+
+fn apply_cli(
+    cli: Option<Res<Cli>>,
+    mut settings: ResMut<CheckpointSettings>,
+    mut restore: EventWriter<RestoreCheckpoint>,
+) {
+    let Some(cli) = cli else {
+        return;
+    };
+    if let Some(directory) = &cli.checkpoints {
+        settings.enabled = true;
+        settings.directory = directory.clone();
+        settings.interval = cli.checkpoint_interval;
+        if cli.resume {
+            restore.send(RestoreCheckpoint(directory.clone()));
+        }
+    }
+}
+
+/// Whether `name` is that of a full checkpoint, an incremental one, or
+/// `None` for any other file, which checkpoints never touch.
+fn checkpoint_kind(name: &str) -> Option<bool> {
+    let (full, rest) = if let Some(rest) = name.strip_prefix("full-") {
+        (true, rest)
+    } else {
+        (false, name.strip_prefix("diff-")?)
+    };
+    let step = rest.strip_suffix(".ron")?;
+    (!step.is_empty() && step.bytes().all(|byte| byte.is_ascii_digit())).then_some(full)
+}
+
+/// Checkpoint files in `directory` ordered by step, full ones and
+/// incremental ones separately.
+fn checkpoint_files(directory: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return default();
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter_map(|path| {
+            let full = checkpoint_kind(path.file_name()?.to_str()?)?;
+            Some((full, path))
+        })
+        .collect();
+    // Steps are zero padded, so names sort chronologically.
+    files.sort();
+    let (fulls, diffs): (Vec<_>, Vec<_>) = files.into_iter().partition(|(full, _)| *full);
+    (
+        fulls.into_iter().map(|(_, path)| path).collect(),
+        diffs.into_iter().map(|(_, path)| path).collect(),
+    )
+}
+
+/// Deletes the checkpoints in `directory` other than the full one named
+/// `keep`, which supersedes them.
+fn remove_superseded(directory: &Path, keep: &str) {
+    let (fulls, diffs) = checkpoint_files(directory);
+    for old in fulls.iter().chain(&diffs) {
+        if old.file_name().is_some_and(|file| file != keep) {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+}
+
+fn write(directory: &Path, checkpoint: &Checkpoint) -> Result<(), String> {
+    std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+    let text = ron::to_string(checkpoint).map_err(|e| e.to_string())?;
+    // Written to a temporary file first, so an interruption never leaves a
+    // truncated checkpoint behind.
+    let path = directory.join(checkpoint.file_name());
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, text).map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, &path).map_err(|e| e.to_string())
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn write_checkpoint(
+    time: Res<Time>,
+    settings: Res<CheckpointSettings>,
+    clock: Res<SimClock>,
+    control: Res<SimulationControl>,
+    rapier_config: Res<RapierConfiguration>,
+    mut base: ResMut<CheckpointBase>,
+    mut log: ResMut<ConsoleLog>,
+    walls: Query<(&Transform, &Wall), Without<ComparisonEntity>>,
+    particles: Query<
+        (Entity, &Transform, &Velocity, &Particle, &RigidBody),
+        Without<ComparisonEntity>,
+    >,
+) {
+    if !settings.enabled {
+        return;
+    }
+    base.elapsed += simulated_delta(&time, &rapier_config);
+    // A reset or loaded scene starts the clock over.
+    if clock.steps < base.step {
+        base.stale = true;
+        base.elapsed = 0.0;
+    }
+    if base.elapsed < settings.interval || clock.steps == 0 {
+        return;
+    }
+    base.elapsed = 0.0;
+
+    let records: HashMap<u64, ParticleRecord> = particles
+        .iter()
+        .map(|(entity, transform, velocity, particle, rigid_body)| {
+            let record = ParticleRecord {
+                position: transform.translation.truncate(),
+                rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+                linvel: velocity.linvel,
+                angvel: velocity.angvel,
+                size: particle.size,
+                pinned: *rigid_body == RigidBody::Fixed,
+            };
+            (entity.to_bits(), record)
+        })
+        .collect();
+    let full =
+        base.stale || base.particles.is_empty() || base.since_full + 1 >= settings.full_every;
+    let checkpoint = if full {
+        Checkpoint {
+            step: clock.steps,
+            base: clock.steps,
+            arena: Some(Arena {
+                gravity: rapier_config.gravity,
+                time_scale: control.time_scale,
+                walls: walls
+                    .iter()
+                    .map(|(transform, wall)| (transform.translation.truncate(), wall.half_extents))
+                    .collect(),
+            }),
+            particles: records.iter().map(|(id, record)| (*id, *record)).collect(),
+            removed: Vec::new(),
+        }
+    } else {
+        Checkpoint {
+            step: clock.steps,
+            base: base.step,
+            arena: None,
+            particles: records
+                .iter()
+                .filter(|(id, record)| base.particles.get(id) != Some(record))
+                .map(|(id, record)| (*id, *record))
+                .collect(),
+            removed: base
+                .particles
+                .keys()
+                .filter(|id| !records.contains_key(id))
+                .copied()
+                .collect(),
+        }
+    };
+
+    if let Err(e) = write(&settings.directory, &checkpoint) {
+        log.category(LogCategory::Scene).warn(format!(
+            "Could not write checkpoint to {}: {e}",
+            settings.directory.display()
+        ));
+        return;
+    }
+    if full {
+        remove_superseded(&settings.directory, &checkpoint.file_name());
+        base.step = clock.steps;
+        base.particles = records;
+        base.since_full = 0;
+        base.stale = false;
+    } else {
+        base.since_full += 1;
+    }
+    log.category(LogCategory::Scene).debug(format!(
+        "Wrote {} checkpoint with {} particles at step {}",
+        if full { "full" } else { "incremental" },
+        checkpoint.particles.len(),
+        clock.steps
+    ));
+}
+
+/// The particles and arena of the latest checkpoint in `directory`.
+fn latest(directory: &Path) -> Result<(Checkpoint, Arena), String> {
+    let (fulls, diffs) = checkpoint_files(directory);
+    let path = fulls.last().ok_or("no full checkpoint")?;
+    let mut checkpoint = Checkpoint::read(path)?;
+    let arena = checkpoint
+        .arena
+        .take()
+        .ok_or("full checkpoint without arena")?;
+    // Incremental checkpoints are cumulative, so only the newest one on top
+    // of the full one matters.
+    let diff = diffs
+        .iter()
+        .rev()
+        .map(|path| Checkpoint::read(path))
+        .find(|diff| {
+            diff.as_ref()
+                .map_or(true, |diff| diff.base == checkpoint.step)
+        })
+        .transpose()?;
+    if let Some(diff) = diff {
+        let mut particles: HashMap<u64, ParticleRecord> =
+            checkpoint.particles.into_iter().collect();
+        for id in &diff.removed {
+            particles.remove(id);
+        }
+        particles.extend(diff.particles);
+        checkpoint.particles = particles.into_iter().collect();
+        checkpoint.step = diff.step;
+    }
+    Ok((checkpoint, arena))
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn restore_checkpoint(
+    mut commands: Commands,
+    mut requests: EventReader<RestoreCheckpoint>,
+    mut log: ResMut<ConsoleLog>,
+    mut particle_counter: ResMut<ParticleCount>,
+    mut clock: ResMut<SimClock>,
+    mut control: ResMut<SimulationControl>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut history: ResMut<EditHistory>,
+    mut rewind: ResMut<RewindBuffer>,
+    mut base: ResMut<CheckpointBase>,
+    particles: Query<Entity, With<Particle>>,
+    walls: Query<Entity, (With<Wall>, Without<ComparisonEntity>)>,
+) {
+    for RestoreCheckpoint(directory) in requests.iter() {
+        let (checkpoint, arena) = match latest(directory) {
+            Ok(latest) => latest,
+            Err(e) => {
+                log.category(LogCategory::Scene).warn(format!(
+                    "Could not restore checkpoint from {}: {e}",
+                    directory.display()
+                ));
+                continue;
+            }
+        };
+        // Comparison twins are despawned as well and get mirrored again
+        // from the restored particles, but the comparison arena stays.
+        for entity in particles.iter().chain(walls.iter()) {
+            commands.entity(entity).despawn_recursive();
+        }
+        history.clear();
+        rewind.clear();
+        rapier_config.gravity = arena.gravity;
+        control.time_scale = arena.time_scale;
+        for (position, half_extents) in arena.walls {
+            spawn_wall(&mut commands, half_extents, position);
+        }
+        particle_counter.0 = 0;
+        for (_, record) in &checkpoint.particles {
+            let particle = ParticleBuilder::at(record.position)
+                .rotation(record.rotation)
+                .size(record.size)
+                .velocity(Velocity {
+                    linvel: record.linvel,
+                    angvel: record.angvel,
+                })
+                .pinned(record.pinned)
+                .build();
+            if let Ok(particle) = particle {
+                commands.spawn(particle);
+                particle_counter.0 += 1;
+            }
+        }
+        clock.steps = checkpoint.step;
+        // The restored particles are new entities, so the next checkpoint
+        // starts over with a full one.
+        *base = CheckpointBase {
+            step: checkpoint.step,
+            stale: true,
+            ..default()
+        };
+        log.category(LogCategory::Scene).info(format!(
+            "Restored {} particles from the checkpoint at step {}",
+            particle_counter.0, checkpoint.step
+        ));
+    }
+}
+
+fn checkpoint_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut settings: ResMut<CheckpointSettings>,
+    mut restore: EventWriter<RestoreCheckpoint>,
+) {
+    egui::Window::new("Checkpoints")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let mut directory = settings.directory.display().to_string();
+            ui.checkbox(&mut settings.enabled, "Write checkpoints");
+            if ui.text_edit_singleline(&mut directory).changed() {
+                settings.directory = PathBuf::from(directory);
+            }
+            ui.add(
+                egui::Slider::new(&mut settings.interval, 1.0..=3600.0)
+                    .logarithmic(true)
+                    .text("Interval (simulated s)"),
+            );
+            ui.add(egui::Slider::new(&mut settings.full_every, 1..=100).text("Full every"));
+            if ui.button("Restore latest").clicked() {
+                restore.send(RestoreCheckpoint(settings.directory.clone()));
+            }
+        });
+}
+
+/// Periodic checkpoints of long runs that can be restored after an
+/// interruption. Most checkpoints only store the particles that changed
+/// since the last full one, which keeps them small when much is at rest.
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CheckpointSettings>()
+            .init_resource::<CheckpointBase>()
+            .add_event::<RestoreCheckpoint>()
+            .add_startup_system(apply_cli)
+            .add_system(restore_checkpoint.after(SceneIo).before(Pipeline::Spawning))
+            .add_system(
+                checkpoint_ui
+                    .label(Pipeline::Ui)
+                    .after(Pipeline::Visual)
+                    .with_run_criteria(ui_available),
+            )
+            .add_system_to_stage(CoreStage::Last, write_checkpoint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(x: f32) -> ParticleRecord {
+        ParticleRecord {
+            position: Vec2::new(x, 0.0),
+            rotation: 0.0,
+            linvel: Vec2::ZERO,
+            angvel: 0.0,
+            size: 4.0,
+            pinned: false,
+        }
+    }
+
+    fn full(step: u64, particles: Vec<(u64, ParticleRecord)>) -> Checkpoint {
+        Checkpoint {
+            step,
+            base: step,
+            arena: Some(Arena {
+                gravity: Vec2::new(0.0, -9810.0),
+                time_scale: 1.0,
+                walls: Vec::new(),
+            }),
+            particles,
+            removed: Vec::new(),
+        }
+    }
+
+    fn diff(
+        step: u64,
+        base: u64,
+        particles: Vec<(u64, ParticleRecord)>,
+        removed: Vec<u64>,
+    ) -> Checkpoint {
+        Checkpoint {
+            step,
+            base,
+            arena: None,
+            particles,
+            removed,
+        }
+    }
+
+    /// Empty directory of its own for every test.
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "physicsboi-checkpoint-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn sorted(particles: Vec<(u64, ParticleRecord)>) -> Vec<(u64, ParticleRecord)> {
+        let mut particles = particles;
+        particles.sort_by_key(|(id, _)| *id);
+        particles
+    }
+
+    #[test]
+    fn only_checkpoint_names_are_recognized() {
+        assert_eq!(checkpoint_kind("full-000000000060.ron"), Some(true));
+        assert_eq!(checkpoint_kind("diff-000000000120.ron"), Some(false));
+        assert_eq!(checkpoint_kind("scene.ron"), None);
+        assert_eq!(checkpoint_kind("full-notes.ron"), None);
+        assert_eq!(checkpoint_kind("diff-.ron"), None);
+        assert_eq!(checkpoint_kind("full-000000000060.partial"), None);
+    }
+
+    #[test]
+    fn latest_applies_the_newest_diff_of_the_latest_full() {
+        let directory = directory("latest");
+        write(
+            &directory,
+            &full(10, vec![(1, record(1.0)), (2, record(2.0))]),
+        )
+        .unwrap();
+        // Built on an older full checkpoint, so ignored.
+        write(&directory, &diff(15, 5, vec![(9, record(9.0))], vec![])).unwrap();
+        write(&directory, &diff(20, 10, vec![(2, record(2.5))], vec![])).unwrap();
+        write(
+            &directory,
+            &diff(30, 10, vec![(2, record(3.0)), (3, record(4.0))], vec![1]),
+        )
+        .unwrap();
+        std::fs::write(directory.join("scene.ron"), "not a checkpoint").unwrap();
+
+        let (checkpoint, arena) = latest(&directory).unwrap();
+        assert_eq!(checkpoint.step, 30);
+        assert_eq!(arena.time_scale, 1.0);
+        assert!(
+            sorted(checkpoint.particles) == vec![(2, record(3.0)), (3, record(4.0))],
+            "diff not merged onto the full checkpoint"
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn latest_without_diff_is_the_full_checkpoint() {
+        let directory = directory("full-only");
+        write(&directory, &full(10, vec![(1, record(1.0))])).unwrap();
+        let (checkpoint, _) = latest(&directory).unwrap();
+        assert_eq!(checkpoint.step, 10);
+        assert!(checkpoint.particles == vec![(1, record(1.0))]);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn a_full_checkpoint_removes_all_other_checkpoints_but_no_other_files() {
+        let directory = directory("retention");
+        write(&directory, &full(10, vec![])).unwrap();
+        write(&directory, &diff(20, 10, vec![], vec![])).unwrap();
+        let newest = full(30, vec![]);
+        write(&directory, &newest).unwrap();
+        std::fs::write(directory.join("scene.ron"), "()").unwrap();
+        std::fs::write(directory.join("diff-notes.ron"), "()").unwrap();
+
+        remove_superseded(&directory, &newest.file_name());
+
+        let mut left: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            ["diff-notes.ron", "full-000000000030.ron", "scene.ron"]
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Startup configuration, so runs can be launched reproducibly from scripts.
+#[derive(Parser, Resource, Clone, Debug)]
+#[command(version, about = "2D particle physics playground")]
+pub struct Cli {
+    /// Window width in logical pixels, overriding the saved size.
+    #[arg(long)]
+    pub width: Option<f32>,
+
+    /// Window height in logical pixels, overriding the saved size.
+    #[arg(long)]
+    pub height: Option<f32>,
+
+    /// Disable vsync and render as fast as possible.
+    #[arg(long)]
+    pub no_vsync: bool,
+
+    /// Scenario name from the library or path to a scene file to start with.
+    #[arg(long)]
+    pub scenario: Option<String>,
+
+    /// Rhai script to run alongside the simulation, see `scripts/`.
+    #[arg(long)]
+    pub script: Option<PathBuf>,
+
+    /// Seed for all spawning randomness. Implies --fixed-timestep.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Maximum number of particles; spawning stops once it is reached.
+    #[arg(long)]
+    pub max_particles: Option<u32>,
+
+    /// Step physics with a fixed timestep instead of following the frame
+    /// rate.
+    #[arg(long)]
+    pub fixed_timestep: bool,
+
+    /// Run without a window and write results to disk.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Simulated seconds to run for in headless mode.
+    #[arg(long, default_value_t = 10.0)]
+    pub duration: f32,
+
+    /// Experiment file to run without a window. The exit status is non-zero
+    /// if any of its assertions fail, see `experiments/`.
+    #[arg(long)]
+    pub experiment: Option<PathBuf>,
+
+    /// Directory to write checkpoints of the simulation to.
+    #[arg(long)]
+    pub checkpoints: Option<PathBuf>,
+
+    /// Simulated seconds between checkpoints.
+    #[arg(long, default_value_t = 60.0)]
+    pub checkpoint_interval: f32,
+
+    /// Continue from the latest checkpoint in `--checkpoints` instead of
+    /// starting the scenario over.
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Sweep file to run without a window, writing a matrix of its metric
+    /// to `<output>.sweep.csv`, see `experiments/`.
+    #[arg(long)]
+    pub sweep: Option<PathBuf>,
+
+    /// Benchmark increasing particle loads without a window and report the
+    /// average frame time per stage.
+    #[arg(long)]
+    pub bench: bool,
+
+    /// Measured steps per load in benchmark mode.
+    #[arg(long, default_value_t = 300)]
+    pub bench_steps: u64,
+
+    /// Address to serve Prometheus metrics on, e.g. `127.0.0.1:9184`.
+    #[cfg(feature = "metrics")]
+    #[arg(long)]
+    pub metrics: Option<String>,
+
+    /// Address to accept remote control WebSocket connections on, e.g.
+    /// `127.0.0.1:9185`.
+    #[cfg(feature = "remote")]
+    #[arg(long)]
+    pub remote: Option<String>,
+
+    /// Also write the final particle state to `<output>.npy` in headless
+    /// mode, for loading with NumPy.
+    #[arg(long)]
+    pub snapshot: bool,
+
+    /// Headless output path; `.csv` and `.ron` are appended, or `.bench.csv`
+    /// when benchmarking and `.sweep.csv` when sweeping.
+    #[arg(long, default_value = "headless")]
+    pub output: PathBuf,
+}
+
+impl Cli {
+    pub fn deterministic(&self) -> bool {
+        self.fixed_timestep || self.seed.is_some()
+    }
+}
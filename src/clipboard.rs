@@ -0,0 +1,158 @@
+use crate::camera::{cursor_world_position, MainCamera};
+use crate::console::{ConsoleLog, LogCategory};
+use crate::input::{Action, ActionInput};
+use crate::selection::Selected;
+use crate::units::from_meters;
+use crate::{Particle, ParticleBuilder, ParticleSpawner, Pipeline};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Gap between a duplicated selection and the original, 1 cm.
+fn duplicate_gap() -> f32 {
+    from_meters(0.01)
+}
+
+/// A copied particle, positioned relative to the center of the copied
+/// selection.
+#[derive(Clone, Copy)]
+struct CopiedParticle {
+    offset: Vec2,
+    builder: ParticleBuilder,
+}
+
+#[derive(Resource, Default)]
+pub struct Clipboard {
+    particles: Vec<CopiedParticle>,
+    /// Width of the copied selection's bounding box.
+    width: f32,
+}
+
+impl Clipboard {
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Returns the clipboard holding `builders` and their center.
+    fn copy(builders: Vec<ParticleBuilder>) -> (Self, Vec2) {
+        let (min, max) = builders.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), builder| {
+                let half = Vec2::splat(builder.size / 2.0);
+                (
+                    min.min(builder.position - half),
+                    max.max(builder.position + half),
+                )
+            },
+        );
+        let center = (min + max) / 2.0;
+        let clipboard = Self {
+            particles: builders
+                .into_iter()
+                .map(|builder| CopiedParticle {
+                    offset: builder.position - center,
+                    builder,
+                })
+                .collect(),
+            width: max.x - min.x,
+        };
+        (clipboard, center)
+    }
+}
+
+/// Spawns the clipboard's particles around `center`, selects them instead of
+/// the current selection and records them as one undoable spawn.
+fn paste(
+    commands: &mut Commands,
+    spawner: &mut ParticleSpawner,
+    clipboard: &Clipboard,
+    center: Vec2,
+    selected: impl Iterator<Item = Entity>,
+    log: &mut ConsoleLog,
+) {
+    for entity in selected {
+        commands.entity(entity).remove::<Selected>();
+    }
+    let pasted = spawner.place(clipboard.particles.iter().map(|copied| ParticleBuilder {
+        position: center + copied.offset,
+        ..copied.builder
+    }));
+    for &entity in &pasted {
+        commands.entity(entity).insert(Selected);
+    }
+    if !pasted.is_empty() {
+        log.category(LogCategory::Edit)
+            .info(format!("Pasted {} particles", pasted.len()));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn clipboard_hotkeys(
+    mut commands: Commands,
+    mut spawner: ParticleSpawner,
+    actions: ActionInput,
+    windows: Res<Windows>,
+    mut clipboard: ResMut<Clipboard>,
+    mut log: ResMut<ConsoleLog>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    selection: Query<(Entity, &Transform, &Velocity, &Particle, &RigidBody), With<Selected>>,
+) {
+    let copied = || {
+        let builders = selection
+            .iter()
+            .map(|(_, transform, velocity, particle, rigid_body)| {
+                ParticleBuilder::at(transform.translation.truncate())
+                    .rotation(transform.rotation.to_euler(EulerRot::XYZ).2)
+                    .size(particle.size)
+                    .velocity(*velocity)
+                    .pinned(*rigid_body == RigidBody::Fixed)
+            })
+            .collect();
+        Clipboard::copy(builders)
+    };
+    let selected = || selection.iter().map(|(entity, ..)| entity);
+
+    if actions.just_pressed(Action::Copy) && !selection.is_empty() {
+        *clipboard = copied().0;
+        log.category(LogCategory::Edit)
+            .info(format!("Copied {} particles", clipboard.particles.len()));
+    }
+    if actions.just_pressed(Action::Paste) && !clipboard.is_empty() {
+        let window = windows.get_primary().unwrap();
+        let (camera, camera_transform) = camera_q.single();
+        if let Some(cursor) = cursor_world_position(window, camera, camera_transform) {
+            paste(
+                &mut commands,
+                &mut spawner,
+                &clipboard,
+                cursor,
+                selected(),
+                &mut log,
+            );
+        }
+    }
+    // Duplicates are placed right next to the original and leave the
+    // clipboard alone.
+    if actions.just_pressed(Action::Duplicate) && !selection.is_empty() {
+        let (duplicate, center) = copied();
+        let offset = Vec2::new(duplicate.width + duplicate_gap(), 0.0);
+        paste(
+            &mut commands,
+            &mut spawner,
+            &duplicate,
+            center + offset,
+            selected(),
+            &mut log,
+        );
+    }
+}
+
+/// Copy, paste and duplicate for the selected particles, keeping their size,
+/// rotation, velocity and whether they are pinned.
+pub struct ClipboardPlugin;
+
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Clipboard>()
+            .add_system(clipboard_hotkeys.label(Pipeline::Input));
+    }
+}
@@ -0,0 +1,322 @@
+use crate::headless::ui_available;
+use crate::input::{Action, ActionInput};
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+}
+
+/// Part of the app a message comes from, so the console can be filtered by
+/// it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogCategory {
+    General,
+    /// Spawning, deleting and other edits of particles.
+    Edit,
+    Scene,
+    Replay,
+    Script,
+    /// Commands received over the remote control socket.
+    Remote,
+    Export,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 7] = [
+        LogCategory::General,
+        LogCategory::Edit,
+        LogCategory::Scene,
+        LogCategory::Replay,
+        LogCategory::Script,
+        LogCategory::Remote,
+        LogCategory::Export,
+    ];
+}
+
+pub struct LogEntry {
+    /// Seconds since startup.
+    pub time: f64,
+    pub level: LogLevel,
+    pub category: LogCategory,
+    pub message: String,
+}
+
+/// Escapes `text` for use inside a JSON string.
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// In-app replacement for printing to stdout. Messages below `verbosity` are
+/// dropped and at most `max_per_second` are kept, so logging from hot paths
+/// stays cheap.
+#[derive(Resource)]
+pub struct ConsoleLog {
+    entries: VecDeque<LogEntry>,
+    pub verbosity: LogLevel,
+    /// Categories left out of the console window.
+    pub hidden: Vec<LogCategory>,
+    pub capacity: usize,
+    pub max_per_second: u32,
+    now: f64,
+    window_start: f64,
+    logged_in_window: u32,
+    suppressed: u32,
+}
+
+impl Default for ConsoleLog {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            verbosity: LogLevel::Info,
+            hidden: Vec::new(),
+            capacity: 500,
+            max_per_second: 20,
+            now: 0.0,
+            window_start: 0.0,
+            logged_in_window: 0,
+            suppressed: 0,
+        }
+    }
+}
+
+impl ConsoleLog {
+    pub fn log(&mut self, level: LogLevel, category: LogCategory, message: impl Into<String>) {
+        if level < self.verbosity {
+            return;
+        }
+        if self.logged_in_window >= self.max_per_second {
+            self.suppressed += 1;
+            return;
+        }
+        self.logged_in_window += 1;
+        self.push(level, category, message.into());
+    }
+
+    pub fn debug(&mut self, message: impl Into<String>) {
+        self.log(LogLevel::Debug, LogCategory::General, message);
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.log(LogLevel::Info, LogCategory::General, message);
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.log(LogLevel::Warn, LogCategory::General, message);
+    }
+
+    /// Logs the following messages in `category`.
+    pub fn category(&mut self, category: LogCategory) -> CategoryLog<'_> {
+        CategoryLog {
+            log: self,
+            category,
+        }
+    }
+
+    /// Writes all kept entries to `path`, as JSON if it ends in `.json` and
+    /// as text lines otherwise.
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let text = if path.extension().is_some_and(|ext| ext == "json") {
+            let entries: Vec<String> = self
+                .entries
+                .iter()
+                .map(|entry| {
+                    format!(
+                        r#"{{"time":{},"level":"{:?}","category":"{:?}","message":"{}"}}"#,
+                        entry.time,
+                        entry.level,
+                        entry.category,
+                        json_escape(&entry.message)
+                    )
+                })
+                .collect();
+            format!("[{}]\n", entries.join(","))
+        } else {
+            let mut text = String::new();
+            for entry in &self.entries {
+                let _ = writeln!(
+                    text,
+                    "[{:8.2}] {:?} {:?}: {}",
+                    entry.time, entry.level, entry.category, entry.message
+                );
+            }
+            text
+        };
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+
+    fn push(&mut self, level: LogLevel, category: LogCategory, message: String) {
+        self.entries.push_back(LogEntry {
+            time: self.now,
+            level,
+            category,
+            message,
+        });
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    fn advance(&mut self, now: f64) {
+        self.now = now;
+        if now - self.window_start < 1.0 {
+            return;
+        }
+        self.window_start = now;
+        self.logged_in_window = 0;
+        if self.suppressed > 0 {
+            let message = format!("{} messages suppressed", self.suppressed);
+            self.suppressed = 0;
+            self.push(LogLevel::Warn, LogCategory::General, message);
+        }
+    }
+}
+
+/// Logs into one category, see [`ConsoleLog::category`].
+pub struct CategoryLog<'a> {
+    log: &'a mut ConsoleLog,
+    category: LogCategory,
+}
+
+impl CategoryLog<'_> {
+    pub fn debug(self, message: impl Into<String>) {
+        self.log.log(LogLevel::Debug, self.category, message);
+    }
+
+    pub fn info(self, message: impl Into<String>) {
+        self.log.log(LogLevel::Info, self.category, message);
+    }
+
+    pub fn warn(self, message: impl Into<String>) {
+        self.log.log(LogLevel::Warn, self.category, message);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ConsoleVisible(pub bool);
+
+/// File the console window exports to.
+#[derive(Resource)]
+struct ConsoleExportPath(String);
+
+impl Default for ConsoleExportPath {
+    fn default() -> Self {
+        Self("console.log".to_string())
+    }
+}
+
+fn advance_console(time: Res<Time>, mut log: ResMut<ConsoleLog>) {
+    log.advance(time.elapsed_seconds_f64());
+}
+
+fn toggle_console(actions: ActionInput, mut visible: ResMut<ConsoleVisible>) {
+    if actions.just_pressed(Action::ToggleConsole) {
+        visible.0 = !visible.0;
+    }
+}
+
+fn console_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut visible: ResMut<ConsoleVisible>,
+    mut log: ResMut<ConsoleLog>,
+    mut export_path: ResMut<ConsoleExportPath>,
+) {
+    let mut open = visible.0;
+    let mut export = false;
+    egui::Window::new("Console")
+        .open(&mut open)
+        .default_width(400.0)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Verbosity:");
+                let verbosity = &mut log.verbosity;
+                ui.selectable_value(verbosity, LogLevel::Debug, "Debug");
+                ui.selectable_value(verbosity, LogLevel::Info, "Info");
+                ui.selectable_value(verbosity, LogLevel::Warn, "Warn");
+            });
+            ui.horizontal_wrapped(|ui| {
+                for category in LogCategory::ALL {
+                    let mut shown = !log.hidden.contains(&category);
+                    if ui.checkbox(&mut shown, format!("{category:?}")).changed() {
+                        if shown {
+                            log.hidden.retain(|hidden| *hidden != category);
+                        } else {
+                            log.hidden.push(category);
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut export_path.0);
+                export = ui.button("Export").clicked();
+            });
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    let shown = log
+                        .entries()
+                        .filter(|e| e.level >= log.verbosity && !log.hidden.contains(&e.category));
+                    for entry in shown {
+                        let color = match entry.level {
+                            LogLevel::Debug => egui::Color32::GRAY,
+                            LogLevel::Info => egui::Color32::LIGHT_GRAY,
+                            LogLevel::Warn => egui::Color32::YELLOW,
+                        };
+                        ui.colored_label(color, format!("[{:8.2}] {}", entry.time, entry.message));
+                    }
+                });
+        });
+    if open != visible.0 {
+        visible.0 = open;
+    }
+    if export {
+        let path = Path::new(&export_path.0);
+        match log.export(path) {
+            Ok(()) => log.info(format!("Exported the console to {}", path.display())),
+            Err(e) => log.warn(format!("Could not export {}: {e}", path.display())),
+        }
+    }
+}
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleLog>()
+            .init_resource::<ConsoleVisible>()
+            .init_resource::<ConsoleExportPath>()
+            .add_system_to_stage(CoreStage::First, advance_console)
+            .add_system(toggle_console.label(Pipeline::Input))
+            .add_system(
+                console_ui
+                    .label(Pipeline::Ui)
+                    .after(Pipeline::Visual)
+                    .with_run_criteria(ui_available)
+                    .after(toggle_console),
+            );
+    }
+}
@@ -0,0 +1,176 @@
+use crate::console::{ConsoleLog, LogCategory};
+use crate::split_screen::ComparisonEntity;
+use crate::units::to_meters;
+use crate::{Particle, Wall};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier2d::prelude::*;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Request to write the current contact graph to a file. `.json` files get
+/// JSON, everything else GraphViz DOT.
+pub struct ExportContactGraph(pub PathBuf);
+
+/// Path entered in the Export window.
+#[derive(Resource)]
+pub struct ContactGraphPath(pub String);
+
+impl Default for ContactGraphPath {
+    fn default() -> Self {
+        Self("contacts.dot".to_string())
+    }
+}
+
+enum NodeKind {
+    Particle { size: f32, speed: f32 },
+    Wall,
+}
+
+struct Node {
+    id: u32,
+    position: Vec2,
+    kind: NodeKind,
+}
+
+impl Node {
+    fn new(entity: Entity, transform: &Transform, kind: NodeKind) -> Self {
+        Self {
+            id: entity.index(),
+            position: transform.translation.truncate(),
+            kind,
+        }
+    }
+}
+
+struct Edge {
+    from: u32,
+    to: u32,
+    /// Number of contact manifolds between the two colliders.
+    manifolds: usize,
+}
+
+fn dot(nodes: &[Node], edges: &[Edge]) -> String {
+    let mut out = String::from("graph contacts {\n");
+    for node in nodes {
+        let (x, y) = (node.position.x, node.position.y);
+        let _ = match node.kind {
+            NodeKind::Particle { size, speed } => writeln!(
+                out,
+                "  {} [kind=particle, x={x:.2}, y={y:.2}, size={size:.2}, speed={speed:.4}];",
+                node.id
+            ),
+            NodeKind::Wall => writeln!(out, "  {} [kind=wall, x={x:.2}, y={y:.2}];", node.id),
+        };
+    }
+    for edge in edges {
+        let _ = writeln!(
+            out,
+            "  {} -- {} [manifolds={}];",
+            edge.from, edge.to, edge.manifolds
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn json(nodes: &[Node], edges: &[Edge]) -> String {
+    let nodes: Vec<String> = nodes
+        .iter()
+        .map(|node| {
+            let (x, y) = (node.position.x, node.position.y);
+            match node.kind {
+                NodeKind::Particle { size, speed } => format!(
+                    r#"{{"id":{},"kind":"particle","x":{x},"y":{y},"size":{size},"speed":{speed}}}"#,
+                    node.id
+                ),
+                NodeKind::Wall => format!(r#"{{"id":{},"kind":"wall","x":{x},"y":{y}}}"#, node.id),
+            }
+        })
+        .collect();
+    let edges: Vec<String> = edges
+        .iter()
+        .map(|edge| {
+            format!(
+                r#"{{"from":{},"to":{},"manifolds":{}}}"#,
+                edge.from, edge.to, edge.manifolds
+            )
+        })
+        .collect();
+    format!(
+        "{{\"nodes\":[{}],\"edges\":[{}]}}\n",
+        nodes.join(","),
+        edges.join(",")
+    )
+}
+
+fn write_graph(path: &Path, nodes: &[Node], edges: &[Edge]) -> Result<(), String> {
+    let text = if path.extension().is_some_and(|ext| ext == "json") {
+        json(nodes, edges)
+    } else {
+        dot(nodes, edges)
+    };
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[allow(clippy::type_complexity)]
+fn export_contact_graph(
+    mut requests: EventReader<ExportContactGraph>,
+    mut log: ResMut<ConsoleLog>,
+    rapier_context: Res<RapierContext>,
+    particles: Query<(Entity, &Transform, &Velocity, &Particle), Without<ComparisonEntity>>,
+    walls: Query<(Entity, &Transform), (With<Wall>, Without<ComparisonEntity>)>,
+) {
+    for ExportContactGraph(path) in requests.iter() {
+        let mut nodes = HashMap::new();
+        for (entity, transform, velocity, particle) in particles.iter() {
+            let kind = NodeKind::Particle {
+                size: particle.size,
+                speed: to_meters(velocity.linvel.length()),
+            };
+            nodes.insert(entity, Node::new(entity, transform, kind));
+        }
+        for (entity, transform) in walls.iter() {
+            nodes.insert(entity, Node::new(entity, transform, NodeKind::Wall));
+        }
+        // Wall to wall contacts do not exist, as walls have no rigid body.
+        let edges: Vec<Edge> = rapier_context
+            .contact_pairs()
+            .filter(|pair| pair.has_any_active_contacts())
+            .filter(|pair| {
+                nodes.contains_key(&pair.collider1()) && nodes.contains_key(&pair.collider2())
+            })
+            .map(|pair| Edge {
+                from: pair.collider1().index(),
+                to: pair.collider2().index(),
+                manifolds: pair.manifolds_len(),
+            })
+            .collect();
+        let mut nodes: Vec<Node> = nodes.drain().map(|(_, node)| node).collect();
+        nodes.sort_by_key(|node| node.id);
+
+        match write_graph(path, &nodes, &edges) {
+            Ok(()) => log.category(LogCategory::Export).info(format!(
+                "Exported {} contacts between {} bodies to {}",
+                edges.len(),
+                nodes.len(),
+                path.display()
+            )),
+            Err(e) => log
+                .category(LogCategory::Export)
+                .warn(format!("Could not export {}: {e}", path.display())),
+        }
+    }
+}
+
+/// Writes the graph of touching bodies on request, for offline analysis of
+/// force chains and clusters in piles.
+pub struct ContactGraphPlugin;
+
+impl Plugin for ContactGraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContactGraphPath>()
+            .add_event::<ExportContactGraph>()
+            .add_system(export_contact_graph);
+    }
+}
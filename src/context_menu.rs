@@ -0,0 +1,231 @@
+use crate::camera::{cursor_world_position, CameraMode, MainCamera};
+use crate::console::{ConsoleLog, LogCategory};
+use crate::history::{EditAction, EditHistory};
+use crate::input::{Action, ActionInput};
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::selection::{particle_at, Selected};
+use crate::spatial::SpatialHash;
+use crate::tools::pointer_over_ui;
+use crate::touch::Probe;
+use crate::units::{format_length, format_speed, to_meters};
+use crate::{Particle, ParticleCount, ParticleState, Pipeline};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+
+/// Particle whose speed and size are drawn next to it until unpinned.
+#[derive(Component)]
+pub struct PinnedProbe;
+
+#[derive(Clone, Copy)]
+enum MenuAction {
+    Probe,
+    TogglePinnedProbe,
+    ToggleFreeze,
+    Delete,
+    Follow,
+}
+
+/// The open particle menu, and the action picked from it, which is carried
+/// out before the next frame's tools run.
+#[derive(Resource, Default)]
+struct ContextMenu {
+    target: Option<Entity>,
+    /// Top left corner of the menu, in egui screen coordinates.
+    position: egui::Pos2,
+    request: Option<(Entity, MenuAction)>,
+}
+
+/// Opens the menu of the particle under the cursor, or closes it when
+/// clicking beside particles.
+fn open_context_menu(
+    actions: ActionInput,
+    windows: Res<Windows>,
+    spatial_hash: Res<SpatialHash>,
+    mut egui_context: ResMut<EguiContext>,
+    mut menu: ResMut<ContextMenu>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    particles: Query<(&Transform, &Particle)>,
+) {
+    if !actions.just_pressed(Action::ContextMenu) || pointer_over_ui(&mut egui_context) {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    menu.target = cursor_world_position(window, camera, camera_transform)
+        .and_then(|cursor| particle_at(&spatial_hash, cursor, &particles));
+    if let Some(cursor) = window.cursor_position() {
+        menu.position = egui::pos2(cursor.x, window.height() - cursor.y);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_menu_action(
+    mut commands: Commands,
+    mut menu: ResMut<ContextMenu>,
+    mut probe: ResMut<Probe>,
+    mut camera_mode: ResMut<CameraMode>,
+    mut history: ResMut<EditHistory>,
+    mut particle_counter: ResMut<ParticleCount>,
+    mut log: ResMut<ConsoleLog>,
+    mut particles: Query<(
+        &Transform,
+        &mut Velocity,
+        &mut RigidBody,
+        &Particle,
+        Option<&PinnedProbe>,
+    )>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    let Some((entity, action)) = menu.request.take() else {
+        return;
+    };
+    let Ok((transform, mut velocity, mut rigid_body, particle, pinned)) = particles.get_mut(entity)
+    else {
+        return;
+    };
+    match action {
+        MenuAction::Probe => probe.0 = Some(entity),
+        MenuAction::TogglePinnedProbe => {
+            if pinned.is_some() {
+                commands.entity(entity).remove::<PinnedProbe>();
+            } else {
+                commands.entity(entity).insert(PinnedProbe);
+            }
+        }
+        MenuAction::ToggleFreeze => {
+            if *rigid_body == RigidBody::Fixed {
+                *rigid_body = RigidBody::Dynamic;
+                log.category(LogCategory::Edit).info("Released 1 particle");
+            } else {
+                *rigid_body = RigidBody::Fixed;
+                *velocity = Velocity::zero();
+                log.category(LogCategory::Edit).info("Pinned 1 particle");
+            }
+        }
+        MenuAction::Delete => {
+            let state = ParticleState {
+                entity,
+                transform: *transform,
+                velocity: *velocity,
+                size: particle.size,
+                pinned: *rigid_body == RigidBody::Fixed,
+            };
+            commands.entity(entity).despawn_recursive();
+            particle_counter.0 = particle_counter.0.saturating_sub(1);
+            log.category(LogCategory::Edit).info("Deleted 1 particle");
+            history.record(EditAction::Delete(vec![state]));
+        }
+        MenuAction::Follow => {
+            for other in selected.iter() {
+                commands.entity(other).remove::<Selected>();
+            }
+            commands.entity(entity).insert(Selected);
+            *camera_mode = CameraMode::Follow;
+        }
+    }
+}
+
+fn context_menu_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut menu: ResMut<ContextMenu>,
+    particles: Query<(&RigidBody, Option<&PinnedProbe>), With<Particle>>,
+) {
+    let Some(entity) = menu.target else {
+        return;
+    };
+    let Ok((rigid_body, pinned)) = particles.get(entity) else {
+        menu.target = None;
+        return;
+    };
+    let frozen = *rigid_body == RigidBody::Fixed;
+    let mut picked = None;
+    let response = egui::Area::new("particle_context_menu")
+        .fixed_pos(menu.position)
+        .order(egui::Order::Foreground)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::Frame::menu(ui.style()).show(ui, |ui| {
+                let entries = [
+                    ("Probe", MenuAction::Probe),
+                    (
+                        if pinned.is_some() {
+                            "Unpin probe"
+                        } else {
+                            "Pin probe"
+                        },
+                        MenuAction::TogglePinnedProbe,
+                    ),
+                    (
+                        if frozen { "Unfreeze" } else { "Freeze" },
+                        MenuAction::ToggleFreeze,
+                    ),
+                    ("Delete", MenuAction::Delete),
+                    ("Follow with camera", MenuAction::Follow),
+                ];
+                for (label, action) in entries {
+                    if ui.button(label).clicked() {
+                        picked = Some(action);
+                    }
+                }
+            });
+        })
+        .response;
+    if let Some(action) = picked {
+        menu.request = Some((entity, action));
+        menu.target = None;
+    } else if response.clicked_elsewhere() {
+        menu.target = None;
+    }
+}
+
+fn draw_pinned_probes(
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    probed: Query<(&Transform, &Velocity, &Particle), With<PinnedProbe>>,
+) {
+    if probed.is_empty() {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let painter = overlay_painter(egui_context.ctx_mut(), "pinned_probes", camera);
+    for (transform, velocity, particle) in probed.iter() {
+        let corner = transform.translation.truncate() + Vec2::splat(particle.size / 2.0);
+        let Some(anchor) = world_to_screen(camera, camera_transform, window, corner) else {
+            continue;
+        };
+        painter.text(
+            anchor + egui::vec2(4.0, 0.0),
+            egui::Align2::LEFT_BOTTOM,
+            format!(
+                "{}\n{}",
+                format_speed(to_meters(velocity.linvel.length())),
+                format_length(particle.size)
+            ),
+            egui::FontId::monospace(11.0),
+            egui::Color32::from_white_alpha(200),
+        );
+    }
+}
+
+/// Menu of actions on a single particle, opened by right-clicking it.
+pub struct ContextMenuPlugin;
+
+impl Plugin for ContextMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContextMenu>()
+            .add_system(handle_menu_action.label(Pipeline::Input))
+            .add_system(
+                open_context_menu
+                    .label(Pipeline::Input)
+                    .after(handle_menu_action),
+            )
+            .add_system(
+                draw_pinned_probes
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            )
+            .add_system(context_menu_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::age::{Age, Lifetime};
+use crate::impacts::Impact;
+use crate::sim_control::simulated_delta;
+use crate::units::{from_meters, from_millimeters};
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Impact energy in joules above which sparks fly.
+const SPARK_ENERGY: f32 = 1e-4;
+/// Sparks per impact at or above [`SPARK_ENERGY`], growing with the energy.
+const MIN_SPARKS: usize = 3;
+const MAX_SPARKS: usize = 12;
+/// Upper bound of live sparks, so a violent pile-up stays cheap.
+const MAX_LIVE_SPARKS: usize = 500;
+/// Simulated seconds a spark lives.
+const SPARK_LIFETIME: f32 = 0.4;
+/// Mean launch speed of a spark in m/s.
+const SPARK_SPEED: f32 = 0.15;
+/// Edge length of a spark in millimeters.
+const SPARK_SIZE_MM: f32 = 1.5;
+
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct EffectSettings {
+    pub sparks: bool,
+}
+
+impl Default for EffectSettings {
+    fn default() -> Self {
+        Self { sparks: true }
+    }
+}
+
+/// Purely visual particle without a collider, moved by [`move_sparks`].
+#[derive(Component)]
+struct Spark {
+    velocity: Vec2,
+}
+
+fn emit_sparks(
+    settings: Res<EffectSettings>,
+    mut commands: Commands,
+    mut impacts: EventReader<Impact>,
+    sparks: Query<(), With<Spark>>,
+) {
+    if !settings.sparks {
+        impacts.clear();
+        return;
+    }
+    let mut live = sparks.iter().count();
+    // Effects must not draw from the simulation's seeded RNG, or turning
+    // them on would change seeded runs.
+    let mut rng = thread_rng();
+    for impact in impacts.iter().filter(|i| i.energy >= SPARK_ENERGY) {
+        let count = (MIN_SPARKS as f32 * (impact.energy / SPARK_ENERGY).log10().max(1.0))
+            .min(MAX_SPARKS as f32) as usize;
+        for _ in 0..count {
+            if live >= MAX_LIVE_SPARKS {
+                return;
+            }
+            live += 1;
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = from_meters(SPARK_SPEED) * rng.gen_range(0.5..1.5);
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        // Above 1 so bloom makes them glow.
+                        color: Color::rgb(4.0, 2.4, 0.8),
+                        custom_size: Some(Vec2::splat(from_millimeters(SPARK_SIZE_MM))),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(impact.position.extend(1.0)),
+                    ..default()
+                },
+                Spark {
+                    velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                },
+                Age::default(),
+                Lifetime(SPARK_LIFETIME * rng.gen_range(0.7..1.3)),
+            ));
+        }
+    }
+}
+
+fn move_sparks(
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut sparks: Query<(&mut Spark, &mut Transform)>,
+) {
+    let delta = simulated_delta(&time, &rapier_config);
+    for (mut spark, mut transform) in sparks.iter_mut() {
+        spark.velocity += rapier_config.gravity * delta;
+        transform.translation += (spark.velocity * delta).extend(0.0);
+    }
+}
+
+fn effects_ui(mut egui_context: ResMut<EguiContext>, mut settings: ResMut<EffectSettings>) {
+    egui::Window::new("Effects")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.checkbox(&mut settings.sparks, "Sparks on hard impacts");
+        });
+}
+
+/// Short-lived visual particles emitted from [`Impact`]s.
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectSettings>()
+            .register_type::<EffectSettings>()
+            .add_system(emit_sparks.after(Pipeline::Spawning))
+            .add_system(move_sparks.after(emit_sparks))
+            .add_system(effects_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::cli::Cli;
+use crate::headless::headless_app;
+use crate::scenarios::resolve;
+use crate::scene::{SceneCommand, SceneIo};
+use crate::sim_control::{SimClock, FIXED_TIMESTEP};
+use crate::stats::{update_stats, SimStats};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Statistic an assertion checks, as in [`crate::stats::StatsSample`].
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Quantity {
+    ParticleCount,
+    KineticEnergy,
+    MeanSpeed,
+    MaxSpeed,
+}
+
+impl Quantity {
+    pub fn measure(self, stats: &SimStats) -> f32 {
+        let current = &stats.current;
+        match self {
+            Quantity::ParticleCount => current.particle_count as f32,
+            Quantity::KineticEnergy => current.kinetic_energy,
+            Quantity::MeanSpeed => current.mean_speed,
+            Quantity::MaxSpeed => current.max_speed,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Check {
+    /// Within the tolerance (second value) of the expected value (first).
+    Within(f32, f32),
+    Below(f32),
+    Above(f32),
+}
+
+impl Check {
+    fn passes(self, value: f32) -> bool {
+        match self {
+            Check::Within(expected, tolerance) => (value - expected).abs() <= tolerance,
+            Check::Below(limit) => value < limit,
+            Check::Above(limit) => value > limit,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Assertion {
+    /// Simulated seconds at which the quantity is checked.
+    pub at: f32,
+    pub quantity: Quantity,
+    pub check: Check,
+}
+
+/// An experiment file: a scene, how long to run it and what must hold.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Experiment {
+    /// Scenario name from the library or path to a scene file.
+    pub scene: String,
+    /// Simulated seconds to run for.
+    pub duration: f32,
+    /// Overrides `--seed`.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub assertions: Vec<Assertion>,
+}
+
+impl Experiment {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::from_str(&text).map_err(|e| e.to_string())
+    }
+}
+
+/// Outcome of one assertion.
+struct Verdict {
+    assertion: Assertion,
+    value: f32,
+    passed: bool,
+}
+
+#[derive(Resource)]
+struct ExperimentRun {
+    scene: PathBuf,
+    /// Simulated seconds to run for.
+    duration: f32,
+    /// Assertions not checked yet.
+    pending: Vec<Assertion>,
+    /// Filled as assertions are checked, since the app is gone once `run`
+    /// returns.
+    verdicts: Arc<Mutex<Vec<Verdict>>>,
+}
+
+fn simulated_time(clock: &SimClock) -> f32 {
+    clock.steps as f32 * FIXED_TIMESTEP
+}
+
+fn start_experiment(run: Res<ExperimentRun>, mut scene_commands: EventWriter<SceneCommand>) {
+    scene_commands.send(SceneCommand::Load(run.scene.clone()));
+}
+
+/// Checks assertions once their time is reached and exits after the
+/// duration.
+fn check_assertions(
+    mut run: ResMut<ExperimentRun>,
+    clock: Res<SimClock>,
+    stats: Res<SimStats>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let now = simulated_time(&clock);
+    let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut run.pending)
+        .into_iter()
+        .partition(|assertion| assertion.at <= now);
+    run.pending = pending;
+    let mut verdicts = run.verdicts.lock().unwrap();
+    for assertion in due {
+        let value = assertion.quantity.measure(&stats);
+        verdicts.push(Verdict {
+            passed: assertion.check.passes(value),
+            assertion,
+            value,
+        });
+    }
+    if now >= run.duration && run.pending.is_empty() {
+        exit.send(AppExit);
+    }
+}
+
+/// Runs the experiment in `cli.experiment` without a window, prints a verdict
+/// per assertion and returns whether all of them passed.
+pub fn run(mut cli: Cli) -> bool {
+    let Some(path) = cli.experiment.clone() else {
+        return false;
+    };
+    let experiment = match Experiment::load(&path) {
+        Ok(experiment) => experiment,
+        Err(e) => {
+            eprintln!("Could not load {}: {e}", path.display());
+            return false;
+        }
+    };
+    let Some(scene) = resolve(&experiment.scene) else {
+        eprintln!("Unknown scenario {}", experiment.scene);
+        return false;
+    };
+
+    cli.seed = experiment.seed.or(cli.seed);
+    // Assertions past the end of the run are checked when it ends.
+    let mut pending = experiment.assertions;
+    for assertion in &mut pending {
+        assertion.at = assertion.at.min(experiment.duration);
+    }
+    let verdicts = Arc::new(Mutex::new(Vec::new()));
+    headless_app(&cli)
+        .insert_resource(ExperimentRun {
+            scene,
+            duration: experiment.duration,
+            pending,
+            verdicts: verdicts.clone(),
+        })
+        .add_startup_system(start_experiment)
+        .add_system(check_assertions.after(SceneIo).after(update_stats))
+        .run();
+
+    let verdicts = verdicts.lock().unwrap();
+    for verdict in verdicts.iter() {
+        let assertion = &verdict.assertion;
+        println!(
+            "{} t={:.2}s {:?} = {} ({:?})",
+            if verdict.passed { "PASS" } else { "FAIL" },
+            assertion.at,
+            assertion.quantity,
+            verdict.value,
+            assertion.check
+        );
+    }
+    let failed = verdicts.iter().filter(|verdict| !verdict.passed).count();
+    println!(
+        "{}: {} of {} assertions passed",
+        path.display(),
+        verdicts.len() - failed,
+        verdicts.len()
+    );
+    failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn within_includes_the_tolerance() {
+        let check = Check::Within(10.0, 0.5);
+        assert!(check.passes(10.0));
+        assert!(check.passes(9.5));
+        assert!(check.passes(10.5));
+        assert!(!check.passes(10.6));
+        assert!(!check.passes(-10.0));
+    }
+
+    #[test]
+    fn limits_are_exclusive() {
+        assert!(Check::Below(1.0).passes(0.99));
+        assert!(!Check::Below(1.0).passes(1.0));
+        assert!(Check::Above(1.0).passes(1.01));
+        assert!(!Check::Above(1.0).passes(1.0));
+    }
+
+    #[test]
+    fn nan_never_passes() {
+        for check in [
+            Check::Within(0.0, 1.0),
+            Check::Below(1.0),
+            Check::Above(-1.0),
+        ] {
+            assert!(!check.passes(f32::NAN));
+        }
+    }
+}
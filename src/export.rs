@@ -0,0 +1,234 @@
+use crate::console::{ConsoleLog, LogCategory};
+use crate::contact_graph::{ContactGraphPath, ExportContactGraph};
+use crate::headless::ui_available;
+use crate::snapshot::{ExportSnapshot, SnapshotPath};
+use crate::split_screen::ComparisonEntity;
+use crate::stats::SimStats;
+use crate::{Particle, Pipeline};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Open output files of a running export.
+struct ExportFiles {
+    stats: BufWriter<File>,
+    particles: Option<BufWriter<File>>,
+    /// Time of the last stats sample written.
+    last_sample: f64,
+    last_particle_dump: f64,
+}
+
+/// Streams statistics to CSV for offline analysis.
+#[derive(Resource)]
+pub struct CsvExport {
+    pub path: String,
+    pub particle_snapshots: bool,
+    /// Seconds between per-particle snapshots.
+    pub particle_interval: f64,
+    files: Option<ExportFiles>,
+}
+
+impl Default for CsvExport {
+    fn default() -> Self {
+        Self {
+            path: "stats.csv".to_string(),
+            particle_snapshots: false,
+            particle_interval: 1.0,
+            files: None,
+        }
+    }
+}
+
+/// Per-particle snapshots are written next to the stats file.
+fn particles_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("stats");
+    path.with_file_name(format!("{stem}_particles.csv"))
+}
+
+fn create_csv(path: &Path, header: &str) -> std::io::Result<BufWriter<File>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "{header}")?;
+    Ok(writer)
+}
+
+impl CsvExport {
+    pub fn is_running(&self) -> bool {
+        self.files.is_some()
+    }
+
+    pub fn start(&mut self) -> std::io::Result<()> {
+        let path = PathBuf::from(&self.path);
+        let stats = create_csv(
+            &path,
+            "time,step,particle_count,kinetic_energy,mean_speed,max_speed",
+        )?;
+        let particles = if self.particle_snapshots {
+            Some(create_csv(
+                &particles_path(&path),
+                "time,entity,x,y,vx,vy,size",
+            )?)
+        } else {
+            None
+        };
+        self.files = Some(ExportFiles {
+            stats,
+            particles,
+            last_sample: f64::NEG_INFINITY,
+            last_particle_dump: f64::NEG_INFINITY,
+        });
+        Ok(())
+    }
+
+    pub fn stop(&mut self) -> std::io::Result<()> {
+        if let Some(mut files) = self.files.take() {
+            files.stats.flush()?;
+            if let Some(particles) = &mut files.particles {
+                particles.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_rows(
+    files: &mut ExportFiles,
+    interval: f64,
+    stats: &SimStats,
+    particles: &Query<(Entity, &Transform, &Velocity, &Particle), Without<ComparisonEntity>>,
+) -> std::io::Result<()> {
+    let last_sample = files.last_sample;
+    for sample in stats.history.iter().filter(|s| s.time > last_sample) {
+        writeln!(
+            files.stats,
+            "{},{},{},{},{},{}",
+            sample.time,
+            sample.step,
+            sample.particle_count,
+            sample.kinetic_energy,
+            sample.mean_speed,
+            sample.max_speed
+        )?;
+        files.last_sample = sample.time;
+    }
+
+    let now = stats.current.time;
+    let Some(writer) = &mut files.particles else {
+        return Ok(());
+    };
+    if now - files.last_particle_dump < interval {
+        return Ok(());
+    }
+    files.last_particle_dump = now;
+    for (entity, transform, velocity, particle) in particles.iter() {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{}",
+            now,
+            entity.index(),
+            transform.translation.x,
+            transform.translation.y,
+            velocity.linvel.x,
+            velocity.linvel.y,
+            particle.size
+        )?;
+    }
+    Ok(())
+}
+
+fn export_rows(
+    mut export: ResMut<CsvExport>,
+    mut log: ResMut<ConsoleLog>,
+    stats: Res<SimStats>,
+    particles: Query<(Entity, &Transform, &Velocity, &Particle), Without<ComparisonEntity>>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+    let interval = export.particle_interval;
+    let Some(files) = &mut export.files else {
+        return;
+    };
+    if let Err(e) = write_rows(files, interval, &stats, &particles) {
+        log.category(LogCategory::Export)
+            .warn(format!("CSV export stopped: {e}"));
+        export.files = None;
+    }
+}
+
+fn export_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut export: ResMut<CsvExport>,
+    mut log: ResMut<ConsoleLog>,
+    mut graph_path: ResMut<ContactGraphPath>,
+    mut graph_requests: EventWriter<ExportContactGraph>,
+    mut snapshot_path: ResMut<SnapshotPath>,
+    mut snapshot_requests: EventWriter<ExportSnapshot>,
+) {
+    egui::Window::new("Export")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let running = export.is_running();
+            ui.add_enabled_ui(!running, |ui| {
+                ui.text_edit_singleline(&mut export.path);
+                ui.checkbox(&mut export.particle_snapshots, "Per-particle snapshots");
+                ui.add_enabled(
+                    export.particle_snapshots,
+                    egui::Slider::new(&mut export.particle_interval, 0.1..=10.0)
+                        .logarithmic(true)
+                        .text("Interval (s)"),
+                );
+            });
+            if ui.button(if running { "Stop" } else { "Start" }).clicked() {
+                let result = if running {
+                    export.stop()
+                } else {
+                    export.start()
+                };
+                match result {
+                    Ok(()) if running => log
+                        .category(LogCategory::Export)
+                        .info(format!("Exported to {}", export.path)),
+                    Ok(()) => log
+                        .category(LogCategory::Export)
+                        .info(format!("Exporting to {}", export.path)),
+                    Err(e) => log
+                        .category(LogCategory::Export)
+                        .warn(format!("CSV export failed: {e}")),
+                }
+            }
+            ui.separator();
+            ui.label("Contact graph (.dot or .json)");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut graph_path.0);
+                if ui.button("Export").clicked() {
+                    graph_requests.send(ExportContactGraph(PathBuf::from(&graph_path.0)));
+                }
+            });
+            ui.separator();
+            ui.label("Particle snapshot (.npy)");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut snapshot_path.0);
+                if ui.button("Export").clicked() {
+                    snapshot_requests.send(ExportSnapshot(PathBuf::from(&snapshot_path.0)));
+                }
+            });
+        });
+}
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CsvExport>()
+            .add_system(export_rows)
+            .add_system(
+                export_ui
+                    .label(Pipeline::Ui)
+                    .after(Pipeline::Visual)
+                    .with_run_criteria(ui_available),
+            );
+    }
+}
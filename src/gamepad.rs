@@ -0,0 +1,87 @@
+use crate::camera::{CameraMode, MainCamera};
+use crate::Pipeline;
+use bevy::prelude::*;
+
+/// Virtual cursor speed at full stick deflection, in logical pixels per second.
+const CURSOR_SPEED: f32 = 600.0;
+/// Camera pan speed at full stick deflection, in screen pixels per second.
+const PAN_SPEED: f32 = 800.0;
+
+/// Combined deflection of one stick over all connected gamepads.
+fn stick(
+    gamepads: &Gamepads,
+    axes: &Axis<GamepadAxis>,
+    x: GamepadAxisType,
+    y: GamepadAxisType,
+) -> Vec2 {
+    gamepads
+        .iter()
+        .map(|gamepad| {
+            Vec2::new(
+                axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.0),
+                axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.0),
+            )
+        })
+        .sum::<Vec2>()
+        .clamp_length_max(1.0)
+}
+
+/// Moves the system cursor with the left stick, so the spawn, measure and
+/// select tools work with a gamepad through their usual actions.
+fn move_cursor(
+    time: Res<Time>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut windows: ResMut<Windows>,
+) {
+    let input = stick(
+        &gamepads,
+        &axes,
+        GamepadAxisType::LeftStickX,
+        GamepadAxisType::LeftStickY,
+    );
+    if input == Vec2::ZERO {
+        return;
+    }
+    let Some(window) = windows.get_primary_mut() else {
+        return;
+    };
+    let size = Vec2::new(window.width(), window.height());
+    let cursor = window.cursor_position().unwrap_or(size / 2.0);
+    let position = cursor + input * CURSOR_SPEED * time.delta_seconds();
+    window.set_cursor_position(position.clamp(Vec2::ZERO, size));
+}
+
+fn pan_camera(
+    time: Res<Time>,
+    mode: Res<CameraMode>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    if *mode != CameraMode::Free {
+        return;
+    }
+    let input = stick(
+        &gamepads,
+        &axes,
+        GamepadAxisType::RightStickX,
+        GamepadAxisType::RightStickY,
+    );
+    if input == Vec2::ZERO {
+        return;
+    }
+    let mut transform = camera_q.single_mut();
+    let delta = input * PAN_SPEED * time.delta_seconds() * transform.scale.x;
+    transform.translation += delta.extend(0.0);
+}
+
+/// Analog stick controls. Buttons go through the regular action bindings.
+pub struct GamepadControlsPlugin;
+
+impl Plugin for GamepadControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(move_cursor.label(Pipeline::Input))
+            .add_system(pan_camera.label(Pipeline::Input));
+    }
+}
@@ -0,0 +1,260 @@
+use crate::console::{ConsoleLog, LogCategory};
+use crate::selection::Selected;
+use crate::units::{format_energy, format_speed, to_meters};
+use crate::{pulse, Particle, Pipeline};
+use bevy::prelude::*;
+use bevy_easings::EasingComponent;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+
+/// Colors handed to new groups in turn.
+const PALETTE: [[f32; 3]; 6] = [
+    [1.0, 0.45, 0.35],
+    [0.35, 0.75, 1.0],
+    [0.5, 1.0, 0.45],
+    [1.0, 0.85, 0.3],
+    [0.85, 0.5, 1.0],
+    [0.3, 1.0, 0.85],
+];
+
+/// Named group a particle was assigned to.
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct GroupMember(pub String);
+
+pub struct Group {
+    pub name: String,
+    pub color: [f32; 3],
+    /// Whether members are drawn in `color` instead of pulsing.
+    pub recolor: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct Groups {
+    pub groups: Vec<Group>,
+    /// Name typed into the groups window for the next assignment.
+    new_name: String,
+}
+
+impl Groups {
+    fn get(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    fn add(&mut self, name: &str) {
+        if self.get(name).is_none() {
+            self.groups.push(Group {
+                name: name.to_string(),
+                color: PALETTE[self.groups.len() % PALETTE.len()],
+                recolor: false,
+            });
+        }
+    }
+}
+
+/// Operation on all members of a group, picked in the groups window.
+#[derive(Clone, Copy)]
+enum GroupOperation {
+    Select,
+    Stop,
+    Pin,
+    Release,
+    Disband,
+    /// Recoloring was turned off, so the members pulse again.
+    Uncolor,
+}
+
+#[derive(Default)]
+struct GroupStats {
+    count: usize,
+    mean_speed: f32,
+    kinetic_energy: f32,
+}
+
+#[allow(clippy::type_complexity)]
+fn group_stats(
+    name: &str,
+    members: &Query<(
+        Entity,
+        &GroupMember,
+        &Particle,
+        &mut Velocity,
+        &mut RigidBody,
+        &ReadMassProperties,
+    )>,
+) -> GroupStats {
+    let mut stats = GroupStats::default();
+    for (_, member, _, velocity, _, mass_properties) in members.iter() {
+        if member.0 != name {
+            continue;
+        }
+        let speed = to_meters(velocity.linvel.length());
+        stats.count += 1;
+        stats.mean_speed += speed;
+        stats.kinetic_energy += 0.5 * mass_properties.0.mass * speed * speed;
+    }
+    if stats.count > 0 {
+        stats.mean_speed /= stats.count as f32;
+    }
+    stats
+}
+
+#[allow(clippy::type_complexity)]
+fn groups_ui(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    mut groups: ResMut<Groups>,
+    mut log: ResMut<ConsoleLog>,
+    selected: Query<Entity, With<Selected>>,
+    mut members: Query<(
+        Entity,
+        &GroupMember,
+        &Particle,
+        &mut Velocity,
+        &mut RigidBody,
+        &ReadMassProperties,
+    )>,
+) {
+    let mut assign = false;
+    let mut operation = None;
+    egui::Window::new("Groups").show(egui_context.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut groups.new_name);
+            let can_assign = !groups.new_name.trim().is_empty() && !selected.is_empty();
+            assign = ui
+                .add_enabled(can_assign, egui::Button::new("Assign selected"))
+                .clicked();
+        });
+        for group in groups.groups.iter_mut() {
+            let stats = group_stats(&group.name, &members);
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.strong(&group.name);
+                ui.label(format!(
+                    "{} particles, {} mean, {}",
+                    stats.count,
+                    format_speed(stats.mean_speed),
+                    format_energy(stats.kinetic_energy)
+                ));
+            });
+            ui.horizontal(|ui| {
+                let recolor = group.recolor;
+                ui.checkbox(&mut group.recolor, "Color");
+                ui.color_edit_button_rgb(&mut group.color);
+                if recolor && !group.recolor {
+                    operation = Some((group.name.clone(), GroupOperation::Uncolor));
+                }
+                for (label, op) in [
+                    ("Select", GroupOperation::Select),
+                    ("Stop", GroupOperation::Stop),
+                    ("Pin", GroupOperation::Pin),
+                    ("Release", GroupOperation::Release),
+                    ("Disband", GroupOperation::Disband),
+                ] {
+                    if ui.button(label).clicked() {
+                        operation = Some((group.name.clone(), op));
+                    }
+                }
+            });
+        }
+    });
+
+    if assign {
+        let name = groups.new_name.trim().to_string();
+        groups.add(&name);
+        for entity in selected.iter() {
+            commands.entity(entity).insert(GroupMember(name.clone()));
+        }
+        log.category(LogCategory::Edit).info(format!(
+            "Assigned {} particles to {name}",
+            selected.iter().len()
+        ));
+    }
+
+    let Some((name, operation)) = operation else {
+        return;
+    };
+    let recolored = groups.get(&name).is_some_and(|group| group.recolor);
+    if let GroupOperation::Select = operation {
+        for entity in selected.iter() {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+    for (entity, member, particle, mut velocity, mut rigid_body, _) in members.iter_mut() {
+        if member.0 != name {
+            continue;
+        }
+        match operation {
+            GroupOperation::Select => {
+                commands.entity(entity).insert(Selected);
+            }
+            GroupOperation::Stop => *velocity = Velocity::zero(),
+            GroupOperation::Pin => {
+                *rigid_body = RigidBody::Fixed;
+                *velocity = Velocity::zero();
+            }
+            GroupOperation::Release => *rigid_body = RigidBody::Dynamic,
+            GroupOperation::Disband => {
+                commands.entity(entity).remove::<GroupMember>();
+                if recolored {
+                    commands.entity(entity).insert(pulse(particle.size));
+                }
+            }
+            GroupOperation::Uncolor => {
+                commands.entity(entity).insert(pulse(particle.size));
+            }
+        }
+    }
+    if let GroupOperation::Disband = operation {
+        groups.groups.retain(|group| group.name != name);
+        log.category(LogCategory::Edit)
+            .info(format!("Disbanded {name}"));
+    }
+}
+
+/// Paints members of recolored groups in their group's color, which needs
+/// the pulsing easing out of the way.
+#[allow(clippy::type_complexity)]
+fn recolor_groups(
+    mut commands: Commands,
+    groups: Res<Groups>,
+    mut members: Query<(
+        Entity,
+        &GroupMember,
+        &Particle,
+        &mut Sprite,
+        Option<&EasingComponent<Sprite>>,
+    )>,
+) {
+    for (entity, member, particle, mut sprite, easing) in members.iter_mut() {
+        let Some(group) = groups.get(&member.0).filter(|group| group.recolor) else {
+            continue;
+        };
+        if easing.is_some() {
+            commands.entity(entity).remove::<EasingComponent<Sprite>>();
+            sprite.custom_size = Some(Vec2::splat(particle.size));
+        }
+        // Keeps the opacity of particles fading out at the end of their
+        // lifetime.
+        let [r, g, b] = group.color;
+        let color = Color::rgba(r, g, b, sprite.color.a());
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+/// Named groups of particles with their own statistics, colors and bulk
+/// operations.
+pub struct GroupsPlugin;
+
+impl Plugin for GroupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Groups>()
+            .add_system(groups_ui.label(Pipeline::Ui).after(Pipeline::Visual))
+            .add_system(
+                recolor_groups
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            );
+    }
+}
@@ -0,0 +1,169 @@
+use crate::annotations::Annotations;
+use crate::checkpoint::CheckpointPlugin;
+use crate::cli::Cli;
+use crate::console::{ConsoleLog, ConsolePlugin, LogCategory};
+use crate::export::{CsvExport, ExportPlugin};
+use crate::history::EditHistory;
+use crate::input::Keybindings;
+use crate::rewind::RewindBuffer;
+use crate::rng::SimulationRngPlugin;
+use crate::scenarios::resolve;
+use crate::scene::{SceneCommand, SceneIo, ScenePlugin};
+use crate::scripting::ScriptingPlugin;
+use crate::sim_control::{SimClock, SimulationControlPlugin, FIXED_TIMESTEP};
+use crate::snapshot::{ExportSnapshot, SnapshotPlugin};
+use crate::solver::SolverPlugin;
+use crate::stats::{update_stats, SimStats};
+use crate::trails::TrailSettings;
+use crate::units::WORLD_UNITS_PER_METER;
+use crate::{ParticlePlugin, Particles};
+use bevy::app::AppExit;
+use bevy::ecs::schedule::ShouldRun;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::EguiContext;
+use bevy_rapier2d::prelude::*;
+use std::path::PathBuf;
+
+/// Run criteria for systems that draw UI, which does not exist headless.
+pub fn ui_available(egui_context: Option<Res<EguiContext>>) -> ShouldRun {
+    if egui_context.is_some() {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Parameters of a batch run without a window.
+#[derive(Resource, Clone)]
+pub struct HeadlessRun {
+    /// Simulated seconds to run for.
+    pub duration: f32,
+    /// Scene to start from instead of the default arena.
+    pub scene: Option<PathBuf>,
+    /// Results are written to `<output>.csv` and `<output>.ron`.
+    pub output: PathBuf,
+    /// Whether to also write the final particles to `<output>.npy`.
+    pub snapshot: bool,
+}
+
+impl HeadlessRun {
+    fn steps(&self) -> u64 {
+        (self.duration / FIXED_TIMESTEP).ceil() as u64
+    }
+}
+
+fn start_run(
+    run: Res<HeadlessRun>,
+    mut export: ResMut<CsvExport>,
+    mut log: ResMut<ConsoleLog>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    if let Some(scene) = &run.scene {
+        scene_commands.send(SceneCommand::Load(scene.clone()));
+    }
+    export.path = run.output.with_extension("csv").display().to_string();
+    if let Err(e) = export.start() {
+        log.category(LogCategory::Export)
+            .warn(format!("Could not write {}: {e}", export.path));
+    }
+}
+
+/// Saves the final scene once the duration is reached, then flushes the
+/// export and exits on the following frame.
+#[allow(clippy::too_many_arguments)]
+fn finish_run(
+    run: Res<HeadlessRun>,
+    clock: Res<SimClock>,
+    mut finishing: Local<bool>,
+    mut export: ResMut<CsvExport>,
+    mut log: ResMut<ConsoleLog>,
+    mut scene_commands: EventWriter<SceneCommand>,
+    mut snapshots: EventWriter<ExportSnapshot>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if *finishing {
+        if let Err(e) = export.stop() {
+            log.category(LogCategory::Export)
+                .warn(format!("Could not write {}: {e}", export.path));
+        }
+        for entry in log.entries() {
+            println!("[{:8.2}] {}", entry.time, entry.message);
+        }
+        exit.send(AppExit);
+    } else if clock.steps >= run.steps() {
+        *finishing = true;
+        scene_commands.send(SceneCommand::Save(run.output.with_extension("ron")));
+        if run.snapshot {
+            snapshots.send(ExportSnapshot(run.output.with_extension("npy")));
+        }
+    }
+}
+
+/// App with the simulation but no window, rendering or UI, shared by the
+/// headless and benchmark runs.
+pub fn headless_app(cli: &Cli) -> App {
+    let mut app = App::new();
+    app.insert_resource(Particles(1))
+        .insert_resource(cli.clone())
+        .add_plugins(MinimalPlugins)
+        .add_plugin(bevy::transform::TransformPlugin)
+        .add_plugin(bevy::hierarchy::HierarchyPlugin)
+        .add_plugin(bevy::input::InputPlugin)
+        .add_plugin(SimulationRngPlugin { seed: cli.seed })
+        .add_plugin(ParticlePlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(SimulationControlPlugin {
+            deterministic: true,
+        })
+        .add_plugin(ScenePlugin)
+        .add_plugin(CheckpointPlugin)
+        .add_plugin(ExportPlugin)
+        .add_plugin(SnapshotPlugin)
+        .add_plugin(ScriptingPlugin)
+        .add_plugin(SolverPlugin)
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(
+            WORLD_UNITS_PER_METER,
+        ))
+        .init_resource::<SimStats>()
+        .init_resource::<Annotations>()
+        .init_resource::<TrailSettings>()
+        .init_resource::<EditHistory>()
+        .init_resource::<RewindBuffer>()
+        .init_resource::<Keybindings>()
+        // Every frame is exactly one physics step, so sample each of them.
+        .add_system(update_stats);
+    #[cfg(feature = "metrics")]
+    app.add_plugin(crate::metrics::MetricsPlugin);
+    #[cfg(feature = "remote")]
+    app.add_plugin(crate::remote::RemotePlugin);
+    app
+}
+
+/// Runs the simulation without window or rendering and writes the results to
+/// disk.
+pub fn run(cli: Cli) {
+    // A resumed run continues with the scene of the checkpoint.
+    let resuming = cli.resume && cli.checkpoints.is_some();
+    let scene = match &cli.scenario {
+        _ if resuming => None,
+        Some(name) => match resolve(name) {
+            Some(path) => Some(path),
+            None => {
+                eprintln!("Unknown scenario {name}");
+                return;
+            }
+        },
+        None => None,
+    };
+    let headless_run = HeadlessRun {
+        duration: cli.duration,
+        scene,
+        output: cli.output.clone(),
+        snapshot: cli.snapshot,
+    };
+    headless_app(&cli)
+        .insert_resource(headless_run)
+        .add_startup_system(start_run)
+        .add_system(finish_run.before(SceneIo))
+        .run();
+}
@@ -0,0 +1,159 @@
+use crate::console::{ConsoleLog, LogCategory};
+use crate::input::{Action, ActionInput};
+use crate::{ParticleCount, ParticleState, Pipeline};
+use bevy::prelude::*;
+
+/// A reversible editing action. Physics evolution is not recorded.
+pub enum EditAction {
+    Spawn(Vec<ParticleState>),
+    Delete(Vec<ParticleState>),
+}
+
+impl EditAction {
+    fn states_mut(&mut self) -> &mut Vec<ParticleState> {
+        match self {
+            EditAction::Spawn(states) | EditAction::Delete(states) => states,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            EditAction::Spawn(states) => format!("spawn of {} particles", states.len()),
+            EditAction::Delete(states) => format!("deletion of {} particles", states.len()),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo: Vec<EditAction>,
+    redo: Vec<EditAction>,
+    /// Whether spawns are still being merged into the last action.
+    stroke_open: bool,
+}
+
+impl EditHistory {
+    pub fn record(&mut self, action: EditAction) {
+        self.stroke_open = false;
+        self.undo.push(action);
+        self.redo.clear();
+    }
+
+    /// Records a spawned particle, grouping everything spawned during one
+    /// mouse stroke into a single action.
+    pub fn record_spawn(&mut self, state: ParticleState) {
+        if self.stroke_open {
+            if let Some(EditAction::Spawn(states)) = self.undo.last_mut() {
+                states.push(state);
+                return;
+            }
+        }
+        self.record(EditAction::Spawn(vec![state]));
+        self.stroke_open = true;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn close_stroke(&mut self) {
+        self.stroke_open = false;
+    }
+
+    /// Replaces references to a respawned particle in all recorded actions.
+    fn remap(&mut self, old: Entity, new: Entity) {
+        for action in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            for state in action.states_mut() {
+                if state.entity == old {
+                    state.entity = new;
+                }
+            }
+        }
+    }
+}
+
+fn despawn_all(
+    commands: &mut Commands,
+    particle_counter: &mut ParticleCount,
+    states: &[ParticleState],
+) {
+    for state in states {
+        if let Some(entity) = commands.get_entity(state.entity) {
+            entity.despawn_recursive();
+            particle_counter.0 = particle_counter.0.saturating_sub(1);
+        }
+    }
+}
+
+fn respawn_all(
+    commands: &mut Commands,
+    particle_counter: &mut ParticleCount,
+    history: &mut EditHistory,
+    action: &mut EditAction,
+) {
+    for state in action.states_mut() {
+        let entity = commands.spawn(state.bundle()).id();
+        particle_counter.0 += 1;
+        history.remap(state.entity, entity);
+        state.entity = entity;
+    }
+}
+
+fn close_spawn_stroke(actions: ActionInput, mut history: ResMut<EditHistory>) {
+    if actions.just_released(Action::UseTool) {
+        history.close_stroke();
+    }
+}
+
+fn undo_redo(
+    mut commands: Commands,
+    actions: ActionInput,
+    mut history: ResMut<EditHistory>,
+    mut particle_counter: ResMut<ParticleCount>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    if actions.just_pressed(Action::Undo) {
+        history.close_stroke();
+        let Some(mut action) = history.undo.pop() else {
+            return;
+        };
+        match &mut action {
+            EditAction::Spawn(states) => despawn_all(&mut commands, &mut particle_counter, states),
+            EditAction::Delete(_) => respawn_all(
+                &mut commands,
+                &mut particle_counter,
+                &mut history,
+                &mut action,
+            ),
+        }
+        log.category(LogCategory::Edit)
+            .info(format!("Undid {}", action.describe()));
+        history.redo.push(action);
+    } else if actions.just_pressed(Action::Redo) {
+        let Some(mut action) = history.redo.pop() else {
+            return;
+        };
+        match &mut action {
+            EditAction::Spawn(_) => respawn_all(
+                &mut commands,
+                &mut particle_counter,
+                &mut history,
+                &mut action,
+            ),
+            EditAction::Delete(states) => despawn_all(&mut commands, &mut particle_counter, states),
+        }
+        log.category(LogCategory::Edit)
+            .info(format!("Redid {}", action.describe()));
+        history.undo.push(action);
+    }
+}
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditHistory>()
+            .add_system(close_spawn_stroke.label(Pipeline::Input))
+            .add_system(undo_redo.label(Pipeline::Input));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::input::{Action, ActionInput};
+use crate::sim_control::SimulationControl;
+use crate::units::format_duration;
+use crate::{ParticleCount, Pipeline};
+use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+
+/// Whether the performance HUD in the top right corner is shown.
+#[derive(Resource)]
+pub struct HudVisible(pub bool);
+
+impl Default for HudVisible {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+fn toggle_hud(actions: ActionInput, mut visible: ResMut<HudVisible>) {
+    if actions.just_pressed(Action::ToggleHud) {
+        visible.0 = !visible.0;
+    }
+}
+
+fn hud_ui(
+    visible: Res<HudVisible>,
+    mut egui_context: ResMut<EguiContext>,
+    diagnostics: Option<Res<Diagnostics>>,
+    particles: Res<ParticleCount>,
+    control: Res<SimulationControl>,
+) {
+    if !visible.0 {
+        return;
+    }
+    let smoothed = |id| {
+        diagnostics
+            .as_ref()
+            .and_then(|d| d.get(id))
+            .and_then(|d| d.smoothed())
+    };
+    let fps = smoothed(FrameTimeDiagnosticsPlugin::FPS)
+        .map_or_else(|| "-".to_string(), |fps| format!("{fps:.0}"));
+    let frame_time = smoothed(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .map_or_else(|| "-".to_string(), |s| format_duration(s as f32));
+    let state = if control.paused {
+        "paused".to_string()
+    } else {
+        format!("{:.2}x", control.time_scale)
+    };
+    let text = format!(
+        "FPS {fps}\nframe {frame_time}\nparticles {}\n{state}",
+        particles.0
+    );
+
+    egui::Area::new("hud")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+        .interactable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(
+                egui::RichText::new(text)
+                    .monospace()
+                    .color(egui::Color32::from_white_alpha(200)),
+            );
+        });
+}
+
+/// Always-on text with frame rate, particle count and simulation state.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HudVisible>()
+            .add_system(toggle_hud.label(Pipeline::Input))
+            .add_system(hud_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::units::to_meters;
+use crate::Particle;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Two bodies started touching, sent for sound and visual effects.
+#[derive(Clone, Copy, Debug)]
+pub struct Impact {
+    pub position: Vec2,
+    /// Kinetic energy in joules lost in a head-on, perfectly inelastic
+    /// collision, an upper bound of what the impact can dissipate.
+    pub energy: f32,
+    /// Size of the particle involved, or of the first of two particles.
+    pub size: f32,
+}
+
+type Body<'a> = (&'a Velocity, &'a ReadMassProperties, &'a Transform);
+
+/// See [`Impact::energy`]. Bodies without a velocity, like walls, are
+/// immovable.
+fn impact_energy(a: Option<Body>, b: Option<Body>) -> f32 {
+    let (velocity, reduced_mass) = match (a, b) {
+        (Some((va, ma, _)), Some((vb, mb, _))) => {
+            let (ma, mb) = (ma.0.mass, mb.0.mass);
+            if ma + mb <= 0.0 {
+                return 0.0;
+            }
+            (va.linvel - vb.linvel, ma * mb / (ma + mb))
+        }
+        (Some((v, m, _)), None) | (None, Some((v, m, _))) => (v.linvel, m.0.mass),
+        (None, None) => return 0.0,
+    };
+    let speed = to_meters(velocity.length());
+    0.5 * reduced_mass * speed * speed
+}
+
+fn detect_impacts(
+    mut collisions: EventReader<CollisionEvent>,
+    mut impacts: EventWriter<Impact>,
+    bodies: Query<Body>,
+    particles: Query<&Particle>,
+) {
+    for event in collisions.iter() {
+        let CollisionEvent::Started(a, b, _) = *event else {
+            continue;
+        };
+        let (body_a, body_b) = (bodies.get(a).ok(), bodies.get(b).ok());
+        let position = match (body_a, body_b) {
+            (Some((_, _, ta)), Some((_, _, tb))) => {
+                (ta.translation.truncate() + tb.translation.truncate()) / 2.0
+            }
+            (Some((_, _, t)), None) | (None, Some((_, _, t))) => t.translation.truncate(),
+            (None, None) => continue,
+        };
+        let Ok(particle) = particles.get(a).or_else(|_| particles.get(b)) else {
+            continue;
+        };
+        impacts.send(Impact {
+            position,
+            energy: impact_energy(body_a, body_b),
+            size: particle.size,
+        });
+    }
+}
+
+/// Turns rapier's collision events between particles and anything else into
+/// [`Impact`]s.
+pub struct ImpactsPlugin;
+
+impl Plugin for ImpactsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Impact>().add_system(detect_impacts);
+    }
+}
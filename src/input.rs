@@ -0,0 +1,529 @@
+use crate::Pipeline;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Everything the user can trigger with a key, mouse or gamepad button.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum Action {
+    /// Spawn particles, measure or fill a lattice, depending on the active tool.
+    UseTool,
+    Select,
+    /// Open the menu of the particle under the cursor.
+    ContextMenu,
+    DeleteSelected,
+    /// Pin selected particles in place, or release them if all are pinned.
+    TogglePin,
+    Copy,
+    Paste,
+    Duplicate,
+    Pan,
+    ZoomIn,
+    ZoomOut,
+    Pause,
+    Step,
+    RewindBack,
+    RewindForward,
+    Undo,
+    Redo,
+    QuickSave,
+    QuickLoad,
+    Reset,
+    ToggleRecording,
+    PlayReplay,
+    ToggleTrails,
+    ToggleGrid,
+    ToggleComparison,
+    ToggleConsole,
+    ToggleHud,
+    OpenMenu,
+    NextTool,
+    PreviousTool,
+    SpawnTool,
+    MeasureTool,
+    LatticeTool,
+    AnnotateTool,
+    ChargeTool,
+    MoreParticles,
+    FewerParticles,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Trigger {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button on any connected gamepad.
+    Gamepad(GamepadButtonType),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        ctrl: false,
+        shift: false,
+        alt: false,
+    };
+
+    pub fn current(keys: &Input<KeyCode>) -> Self {
+        Self {
+            ctrl: keys.any_pressed([KeyCode::LControl, KeyCode::RControl]),
+            shift: keys.any_pressed([KeyCode::LShift, KeyCode::RShift]),
+            alt: keys.any_pressed([KeyCode::LAlt, KeyCode::RAlt]),
+        }
+    }
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::LControl
+            | KeyCode::RControl
+            | KeyCode::LShift
+            | KeyCode::RShift
+            | KeyCode::LAlt
+            | KeyCode::RAlt
+    )
+}
+
+/// A trigger together with the modifiers that have to be held with it.
+/// Modifiers match exactly, so Shift+Click does not also count as a click.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Binding {
+    pub trigger: Trigger,
+    pub modifiers: Modifiers,
+}
+
+impl Binding {
+    const fn key(key: KeyCode) -> Self {
+        Self {
+            trigger: Trigger::Key(key),
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    const fn mouse(button: MouseButton) -> Self {
+        Self {
+            trigger: Trigger::Mouse(button),
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    const fn gamepad(button: GamepadButtonType) -> Self {
+        Self {
+            trigger: Trigger::Gamepad(button),
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    const fn with(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.modifiers.ctrl {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.shift {
+            label.push_str("Shift+");
+        }
+        if self.modifiers.alt {
+            label.push_str("Alt+");
+        }
+        match self.trigger {
+            Trigger::Key(key) => label.push_str(&format!("{key:?}")),
+            Trigger::Mouse(button) => label.push_str(&format!("{button:?} mouse")),
+            Trigger::Gamepad(button) => label.push_str(&format!("{button:?} (gamepad)")),
+        }
+        label
+    }
+}
+
+const CTRL: Modifiers = Modifiers {
+    ctrl: true,
+    ..Modifiers::NONE
+};
+const SHIFT: Modifiers = Modifiers {
+    shift: true,
+    ..Modifiers::NONE
+};
+const ALT: Modifiers = Modifiers {
+    alt: true,
+    ..Modifiers::NONE
+};
+
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keybindings(pub BTreeMap<Action, Vec<Binding>>);
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        use Action::*;
+        use GamepadButtonType as Pad;
+        let bindings = [
+            (
+                UseTool,
+                vec![
+                    Binding::mouse(MouseButton::Left),
+                    Binding::gamepad(Pad::RightTrigger2),
+                ],
+            ),
+            (
+                Select,
+                vec![
+                    Binding::mouse(MouseButton::Left).with(SHIFT),
+                    Binding::gamepad(Pad::LeftTrigger2),
+                ],
+            ),
+            (ContextMenu, vec![Binding::mouse(MouseButton::Right)]),
+            (
+                DeleteSelected,
+                vec![Binding::key(KeyCode::Delete), Binding::gamepad(Pad::East)],
+            ),
+            (TogglePin, vec![Binding::key(KeyCode::P)]),
+            (Copy, vec![Binding::key(KeyCode::C).with(CTRL)]),
+            (Paste, vec![Binding::key(KeyCode::V).with(CTRL)]),
+            (Duplicate, vec![Binding::key(KeyCode::D).with(CTRL)]),
+            (
+                Pan,
+                vec![
+                    Binding::mouse(MouseButton::Middle),
+                    Binding::mouse(MouseButton::Right).with(ALT),
+                ],
+            ),
+            (
+                ZoomIn,
+                vec![
+                    Binding::key(KeyCode::Equals),
+                    Binding::gamepad(Pad::RightTrigger),
+                ],
+            ),
+            (
+                ZoomOut,
+                vec![
+                    Binding::key(KeyCode::Minus),
+                    Binding::gamepad(Pad::LeftTrigger),
+                ],
+            ),
+            (
+                Pause,
+                vec![Binding::key(KeyCode::Space), Binding::gamepad(Pad::Start)],
+            ),
+            (Step, vec![Binding::key(KeyCode::Period)]),
+            (RewindBack, vec![Binding::key(KeyCode::Left)]),
+            (RewindForward, vec![Binding::key(KeyCode::Right)]),
+            (
+                Undo,
+                vec![
+                    Binding::key(KeyCode::Z).with(CTRL),
+                    Binding::gamepad(Pad::West),
+                ],
+            ),
+            (
+                Redo,
+                vec![
+                    Binding::key(KeyCode::Y).with(CTRL),
+                    Binding::gamepad(Pad::North),
+                ],
+            ),
+            (QuickSave, vec![Binding::key(KeyCode::F5)]),
+            (QuickLoad, vec![Binding::key(KeyCode::F9)]),
+            (Reset, vec![Binding::key(KeyCode::R).with(CTRL)]),
+            (ToggleRecording, vec![Binding::key(KeyCode::F6)]),
+            (PlayReplay, vec![Binding::key(KeyCode::F7)]),
+            (ToggleTrails, vec![Binding::key(KeyCode::T)]),
+            (ToggleGrid, vec![Binding::key(KeyCode::G)]),
+            (ToggleComparison, vec![Binding::key(KeyCode::F2)]),
+            (ToggleConsole, vec![Binding::key(KeyCode::Grave)]),
+            (ToggleHud, vec![Binding::key(KeyCode::F3)]),
+            (OpenMenu, vec![Binding::key(KeyCode::Escape)]),
+            (NextTool, vec![Binding::gamepad(Pad::DPadRight)]),
+            (PreviousTool, vec![Binding::gamepad(Pad::DPadLeft)]),
+            (SpawnTool, vec![Binding::key(KeyCode::Key1)]),
+            (MeasureTool, vec![Binding::key(KeyCode::Key2)]),
+            (LatticeTool, vec![Binding::key(KeyCode::Key3)]),
+            (AnnotateTool, vec![Binding::key(KeyCode::Key4)]),
+            (ChargeTool, vec![Binding::key(KeyCode::Key5)]),
+            (MoreParticles, vec![Binding::gamepad(Pad::DPadUp)]),
+            (FewerParticles, vec![Binding::gamepad(Pad::DPadDown)]),
+        ];
+        Self(bindings.into_iter().collect())
+    }
+}
+
+impl Keybindings {
+    /// Adds default bindings for actions missing from a loaded file, e.g.
+    /// actions introduced after it was saved.
+    pub fn fill_missing(&mut self) {
+        for (action, bindings) in Self::default().0 {
+            self.0.entry(action).or_insert(bindings);
+        }
+    }
+
+    /// Updates bindings saved under an older settings `version` whose
+    /// defaults have since moved.
+    pub fn migrate(&mut self, version: u32) {
+        if version < 1 {
+            // The right mouse button opens the context menu now, and panning
+            // moved to Alt+Right.
+            let old = Binding::mouse(MouseButton::Right);
+            if let Some(pan) = self.0.get_mut(&Action::Pan) {
+                if pan.contains(&old) {
+                    pan.retain(|binding| *binding != old);
+                    if !pan.contains(&old.with(ALT)) {
+                        pan.push(old.with(ALT));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Human-readable list of the bindings of `action`, for tooltips.
+    pub fn label(&self, action: Action) -> String {
+        self.0
+            .get(&action)
+            .map(|bindings| {
+                bindings
+                    .iter()
+                    .map(Binding::label)
+                    .collect::<Vec<_>>()
+                    .join(" / ")
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Action-level view of keyboard, mouse and gamepad input.
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    bindings: Res<'w, Keybindings>,
+    keys: Res<'w, Input<KeyCode>>,
+    mouse: Res<'w, Input<MouseButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    #[system_param(ignore)]
+    _marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's> ActionInput<'w, 's> {
+    fn any(&self, action: Action, check: impl Fn(Trigger) -> bool, modifiers: bool) -> bool {
+        let current = Modifiers::current(&self.keys);
+        self.bindings.0.get(&action).is_some_and(|bindings| {
+            bindings
+                .iter()
+                .any(|b| (!modifiers || b.modifiers == current) && check(b.trigger))
+        })
+    }
+
+    fn gamepad_buttons(
+        &self,
+        button: GamepadButtonType,
+    ) -> impl Iterator<Item = GamepadButton> + '_ {
+        self.gamepads
+            .iter()
+            .map(move |gamepad| GamepadButton::new(gamepad, button))
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.any(
+            action,
+            |trigger| match trigger {
+                Trigger::Key(key) => self.keys.pressed(key),
+                Trigger::Mouse(button) => self.mouse.pressed(button),
+                Trigger::Gamepad(button) => self
+                    .gamepad_buttons(button)
+                    .any(|b| self.gamepad_buttons.pressed(b)),
+            },
+            true,
+        )
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.any(
+            action,
+            |trigger| match trigger {
+                Trigger::Key(key) => self.keys.just_pressed(key),
+                Trigger::Mouse(button) => self.mouse.just_pressed(button),
+                Trigger::Gamepad(button) => self
+                    .gamepad_buttons(button)
+                    .any(|b| self.gamepad_buttons.just_pressed(b)),
+            },
+            true,
+        )
+    }
+
+    /// Releases count regardless of the modifiers held at that moment.
+    pub fn just_released(&self, action: Action) -> bool {
+        self.any(
+            action,
+            |trigger| match trigger {
+                Trigger::Key(key) => self.keys.just_released(key),
+                Trigger::Mouse(button) => self.mouse.just_released(button),
+                Trigger::Gamepad(button) => self
+                    .gamepad_buttons(button)
+                    .any(|b| self.gamepad_buttons.just_released(b)),
+            },
+            false,
+        )
+    }
+}
+
+/// Action waiting for its new binding in the remap window.
+#[derive(Resource, Default)]
+struct Rebinding(Option<Action>);
+
+fn capture_binding(
+    keys: &Input<KeyCode>,
+    mouse: &Input<MouseButton>,
+    gamepad_buttons: &Input<GamepadButton>,
+) -> Option<Binding> {
+    let trigger = keys
+        .get_just_pressed()
+        .find(|key| !is_modifier(**key))
+        .map(|key| Trigger::Key(*key))
+        .or_else(|| mouse.get_just_pressed().next().map(|b| Trigger::Mouse(*b)))
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|b| Trigger::Gamepad(b.button_type))
+        })?;
+    Some(Binding {
+        trigger,
+        modifiers: Modifiers::current(keys),
+    })
+}
+
+/// Binds the first key or button pressed while an action waits for it. The
+/// press is consumed, so it does not also trigger its current action, e.g.
+/// Escape cancelling the capture does not open the menu too.
+fn capture_rebinding(
+    mut bindings: ResMut<Keybindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut mouse: ResMut<Input<MouseButton>>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    if keys.clear_just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+    let Some(binding) = capture_binding(&keys, &mouse, &gamepad_buttons) else {
+        return;
+    };
+    match binding.trigger {
+        Trigger::Key(key) => {
+            keys.clear_just_pressed(key);
+        }
+        Trigger::Mouse(button) => {
+            mouse.clear_just_pressed(button);
+        }
+        Trigger::Gamepad(button_type) => {
+            let pressed: Vec<_> = gamepad_buttons
+                .get_just_pressed()
+                .filter(|button| button.button_type == button_type)
+                .copied()
+                .collect();
+            for button in pressed {
+                gamepad_buttons.clear_just_pressed(button);
+            }
+        }
+    }
+    bindings.0.insert(action, vec![binding]);
+    rebinding.0 = None;
+}
+
+fn keybindings_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut bindings: ResMut<Keybindings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    egui::Window::new("Keybindings")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::Grid::new("keybindings").striped(true).show(ui, |ui| {
+                let actions: Vec<_> = bindings.0.keys().copied().collect();
+                for action in actions {
+                    ui.label(format!("{action:?}"));
+                    let label = if rebinding.0 == Some(action) {
+                        "Press a key or button (Esc cancels)".to_string()
+                    } else {
+                        bindings.label(action)
+                    };
+                    if ui.button(label).clicked() {
+                        rebinding.0 = Some(action);
+                    }
+                    ui.end_row();
+                }
+            });
+            if ui.button("Reset to defaults").clicked() {
+                *bindings = Keybindings::default();
+                rebinding.0 = None;
+            }
+        });
+}
+
+pub struct InputMappingPlugin;
+
+impl Plugin for InputMappingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Keybindings>()
+            .init_resource::<Rebinding>()
+            .add_system(capture_rebinding.before(Pipeline::Input))
+            .add_system(keybindings_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_missing_adds_defaults_for_new_actions_only() {
+        let custom = vec![Binding::key(KeyCode::Q)];
+        let mut bindings = Keybindings(BTreeMap::from([
+            (Action::Pause, custom.clone()),
+            (Action::Undo, Vec::new()),
+        ]));
+        bindings.fill_missing();
+
+        assert_eq!(bindings.0[&Action::Pause], custom);
+        assert!(bindings.0[&Action::Undo].is_empty());
+        let defaults = Keybindings::default();
+        assert_eq!(bindings.0.len(), defaults.0.len());
+        assert_eq!(bindings.0[&Action::Redo], defaults.0[&Action::Redo]);
+        assert_eq!(
+            bindings.0[&Action::ChargeTool],
+            defaults.0[&Action::ChargeTool]
+        );
+    }
+
+    #[test]
+    fn migrate_moves_the_old_right_mouse_pan_to_alt() {
+        let old_pan = vec![
+            Binding::mouse(MouseButton::Middle),
+            Binding::mouse(MouseButton::Right),
+        ];
+        let mut bindings = Keybindings(BTreeMap::from([(Action::Pan, old_pan.clone())]));
+        bindings.migrate(0);
+        assert_eq!(
+            bindings.0[&Action::Pan],
+            Keybindings::default().0[&Action::Pan]
+        );
+
+        let mut current = Keybindings(BTreeMap::from([(Action::Pan, old_pan.clone())]));
+        current.migrate(1);
+        assert_eq!(current.0[&Action::Pan], old_pan);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::camera::{cursor_world_position, MainCamera};
+use crate::console::{ConsoleLog, LogCategory};
+use crate::input::{Action, ActionInput};
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::sizing::MIN_PARTICLE_SIZE;
+use crate::tools::{pointer_over_ui, Tool};
+use crate::units::from_millimeters;
+use crate::{ParticleBuilder, ParticleSpawner, Pipeline};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use rand::Rng;
+
+/// Upper bound on particles in one lattice, so a huge rectangle at a small
+/// size does not stall the app.
+const MAX_LATTICE_PARTICLES: usize = 5000;
+
+#[derive(Resource, Clone, PartialEq)]
+pub struct LatticeSettings {
+    /// Edge length of every particle, in world units.
+    pub size: f32,
+    /// Free space between neighbors, in world units.
+    pub gap: f32,
+    /// Largest random displacement of a particle, as a fraction of the gap.
+    pub jitter: f32,
+}
+
+impl Default for LatticeSettings {
+    fn default() -> Self {
+        Self {
+            size: from_millimeters(4.0),
+            gap: from_millimeters(0.5),
+            jitter: 0.0,
+        }
+    }
+}
+
+/// Corners of the rectangle being dragged with the lattice tool.
+#[derive(Resource, Default)]
+struct LatticeDrag {
+    start: Option<Vec2>,
+    end: Option<Vec2>,
+}
+
+/// Centers of a hexagonal close packing of the rectangle between `a` and `b`:
+/// rows are `sqrt(3) / 2` pitches apart and every other row is shifted by
+/// half a pitch.
+fn hexagonal_positions(a: Vec2, b: Vec2, settings: &LatticeSettings) -> Vec<Vec2> {
+    let (min, max) = (a.min(b), a.max(b));
+    let half = settings.size / 2.0;
+    let pitch = settings.size + settings.gap;
+    let row_height = pitch * 3f32.sqrt() / 2.0;
+    let mut positions = Vec::new();
+    let mut row = 0;
+    let mut y = min.y + half;
+    while y + half <= max.y {
+        let mut x = min.x + half + if row % 2 == 1 { pitch / 2.0 } else { 0.0 };
+        while x + half <= max.x {
+            if positions.len() == MAX_LATTICE_PARTICLES {
+                return positions;
+            }
+            positions.push(Vec2::new(x, y));
+            x += pitch;
+        }
+        y += row_height;
+        row += 1;
+    }
+    positions
+}
+
+#[allow(clippy::too_many_arguments)]
+fn lattice_tool(
+    mut spawner: ParticleSpawner,
+    tool: Res<Tool>,
+    actions: ActionInput,
+    settings: Res<LatticeSettings>,
+    windows: Res<Windows>,
+    mut egui_context: ResMut<EguiContext>,
+    mut drag: ResMut<LatticeDrag>,
+    mut log: ResMut<ConsoleLog>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    if *tool != Tool::Lattice {
+        if drag.start.is_some() {
+            *drag = default();
+        }
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    if let Some(cursor) = cursor_world_position(window, camera, camera_transform) {
+        if actions.just_pressed(Action::UseTool) && !pointer_over_ui(&mut egui_context) {
+            drag.start = Some(cursor);
+        }
+        if drag.start.is_some() && actions.pressed(Action::UseTool) {
+            drag.end = Some(cursor);
+        }
+    }
+    if !actions.just_released(Action::UseTool) {
+        return;
+    }
+    let (Some(start), Some(end)) = (drag.start.take(), drag.end.take()) else {
+        return;
+    };
+
+    let max_offset = settings.jitter * settings.gap / 2.0;
+    // Replays record the placed particles, so the jitter must not draw from
+    // the simulation's generator, which playback would then not advance.
+    let mut rng = rand::thread_rng();
+    let builders = hexagonal_positions(start, end, &settings)
+        .into_iter()
+        .map(|mut position| {
+            if max_offset > 0.0 {
+                position += Vec2::new(
+                    rng.gen_range(-max_offset..=max_offset),
+                    rng.gen_range(-max_offset..=max_offset),
+                );
+            }
+            ParticleBuilder::at(position).size(settings.size)
+        });
+    let spawned = spawner.place(builders).len();
+    if spawned > 0 {
+        log.category(LogCategory::Edit)
+            .info(format!("Filled a lattice of {spawned} particles"));
+    }
+}
+
+fn draw_lattice_drag(
+    drag: Res<LatticeDrag>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    let (Some(start), Some(end)) = (drag.start, drag.end) else {
+        return;
+    };
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let (Some(a), Some(b)) = (
+        world_to_screen(camera, camera_transform, window, start),
+        world_to_screen(camera, camera_transform, window, end),
+    ) else {
+        return;
+    };
+    let painter = overlay_painter(egui_context.ctx_mut(), "lattice", camera);
+    painter.rect_stroke(
+        egui::Rect::from_two_pos(a, b),
+        0.0,
+        egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE),
+    );
+}
+
+fn lattice_ui(
+    tool: Res<Tool>,
+    mut egui_context: ResMut<EguiContext>,
+    mut settings: ResMut<LatticeSettings>,
+) {
+    if *tool != Tool::Lattice {
+        return;
+    }
+    let mut edited = settings.clone();
+    egui::Window::new("Lattice").show(egui_context.ctx_mut(), |ui| {
+        ui.add(
+            egui::Slider::new(&mut edited.size, MIN_PARTICLE_SIZE..=from_millimeters(40.0))
+                .text("Particle size"),
+        );
+        ui.add(egui::Slider::new(&mut edited.gap, 0.0..=from_millimeters(10.0)).text("Gap"));
+        ui.add(egui::Slider::new(&mut edited.jitter, 0.0..=1.0).text("Jitter"));
+        ui.label("Drag a rectangle to fill it.");
+    });
+    if edited != *settings {
+        *settings = edited;
+    }
+}
+
+/// Tool filling a dragged rectangle with a hexagonal close packing of equal
+/// particles at rest, a ready-made solid block.
+pub struct LatticePlugin;
+
+impl Plugin for LatticePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LatticeSettings>()
+            .init_resource::<LatticeDrag>()
+            .add_system(lattice_tool.label(Pipeline::Input))
+            .add_system(
+                draw_lattice_drag
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            )
+            .add_system(lattice_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
@@ -0,0 +1,674 @@
+//! 2D particle physics playground, split into plugins so the simulation can
+//! be embedded in other Bevy apps.
+
+pub mod age;
+pub mod annotations;
+pub mod audio;
+pub mod autosave;
+pub mod bench;
+pub mod camera;
+pub mod charge;
+pub mod checkpoint;
+pub mod cli;
+pub mod clipboard;
+pub mod console;
+pub mod contact_graph;
+pub mod context_menu;
+pub mod effects;
+pub mod experiment;
+pub mod export;
+pub mod gamepad;
+pub mod groups;
+pub mod headless;
+pub mod history;
+pub mod hud;
+pub mod impacts;
+pub mod input;
+pub mod lattice;
+pub mod menu;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod overlay;
+pub mod placement;
+pub mod quality;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod replay;
+pub mod rewind;
+pub mod rng;
+pub mod ruler;
+pub mod scenarios;
+pub mod scene;
+pub mod scripting;
+pub mod selection;
+pub mod settings;
+pub mod sim_control;
+pub mod sizing;
+pub mod snapshot;
+pub mod solver;
+pub mod spatial;
+pub mod spawn_preview;
+pub mod split_screen;
+pub mod stats;
+pub mod sweep;
+pub mod tabs;
+pub mod tools;
+pub mod tooltip;
+pub mod touch;
+pub mod trails;
+pub mod units;
+
+use age::{Age, AgePlugin};
+use annotations::AnnotationsPlugin;
+use audio::AudioFeedbackPlugin;
+use autosave::AutosavePlugin;
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::*;
+use bevy::prelude::*;
+use bevy_easings::*;
+use bevy_inspector_egui::bevy_egui::EguiContext;
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_rapier2d::prelude::*;
+use camera::{cursor_world_position, modifier_pressed, CameraControlsPlugin, MainCamera};
+use charge::ChargePlugin;
+use checkpoint::CheckpointPlugin;
+use cli::Cli;
+use clipboard::ClipboardPlugin;
+use console::{ConsoleLog, ConsolePlugin, LogCategory};
+use contact_graph::ContactGraphPlugin;
+use context_menu::ContextMenuPlugin;
+use effects::EffectsPlugin;
+use export::ExportPlugin;
+use gamepad::GamepadControlsPlugin;
+use groups::GroupsPlugin;
+use history::{EditAction, EditHistory, HistoryPlugin};
+use hud::HudPlugin;
+use impacts::ImpactsPlugin;
+use input::{Action, ActionInput, InputMappingPlugin};
+use lattice::LatticePlugin;
+use menu::MenuPlugin;
+use placement::{Placement, PlacementPlugin};
+use quality::{QualityPlugin, SpawnCap};
+use rand::prelude::*;
+use replay::ReplayPlugin;
+use rewind::RewindPlugin;
+use rng::{SimulationRng, SimulationRngPlugin};
+use ruler::RulerPlugin;
+use scenarios::ScenariosPlugin;
+use scene::ScenePlugin;
+use scripting::ScriptingPlugin;
+use selection::SelectionPlugin;
+use sim_control::SimulationControlPlugin;
+use snapshot::SnapshotPlugin;
+use solver::SolverPlugin;
+use spatial::SpatialHashPlugin;
+use spawn_preview::{SpawnCountIndicator, SpawnPreviewPlugin};
+use split_screen::SplitScreenPlugin;
+use stats::StatsPlugin;
+use std::time::Duration;
+use tabs::TabsPlugin;
+use tools::{pointer_over_ui, Tool, ToolsPlugin};
+use tooltip::TooltipPlugin;
+use touch::TouchPlugin;
+use trails::{Trail, TrailsPlugin};
+
+/// Bloom strength of the main and comparison cameras.
+pub const BLOOM_INTENSITY: f32 = 1.5;
+
+#[derive(Component)]
+pub struct Particle {
+    pub size: f32,
+}
+
+/// Static box collider making up the arena.
+#[derive(Component)]
+pub struct Wall {
+    pub half_extents: Vec2,
+}
+
+/// Everything needed to recreate a particle.
+#[derive(Clone, Copy)]
+pub struct ParticleState {
+    pub entity: Entity,
+    pub transform: Transform,
+    pub velocity: Velocity,
+    pub size: f32,
+    /// Fixed in place instead of simulated.
+    pub pinned: bool,
+}
+
+impl ParticleState {
+    /// Bundle recreating this particle. States are only recorded from live
+    /// particles, so they always pass validation.
+    pub fn bundle(&self) -> PositionedParticle {
+        ParticleBuilder::from_state(self)
+            .build()
+            .expect("recorded particle state is valid")
+    }
+}
+
+#[derive(Bundle)]
+pub struct PositionedParticle {
+    particle: Particle,
+    rigid_body: RigidBody,
+    easing: EasingComponent<Sprite>,
+    collider: Collider,
+    restitution: Restitution,
+    velocity: Velocity,
+    mass_properties: ReadMassProperties,
+    active_events: ActiveEvents,
+    /// Set from [`solver::SolverSettings`] once spawned.
+    ccd: Ccd,
+    sleeping: Sleeping,
+    trail: Trail,
+    age: Age,
+
+    #[bundle]
+    sprite: SpriteBundle,
+}
+
+/// Speed of particles launched with [`ParticleBuilder::scattered`], 0.1 m/s.
+const LAUNCH_SPEED: f32 = 0.1 * units::WORLD_UNITS_PER_METER;
+/// Edge lengths in millimeters of particles spawned by a click, picked
+/// uniformly.
+pub const SPAWN_SIZES_MM: std::ops::Range<i32> = 1..8;
+/// Resting color of a particle, before it is recolored by a group.
+pub const PARTICLE_COLOR: Color = Color::rgb(0.75, 0.75, 0.75);
+/// Distance from the spawn position particles launched with
+/// [`ParticleBuilder::scattered`] start at.
+pub const SCATTER_DISTANCE: f32 = 0.2 * LAUNCH_SPEED;
+
+/// Constructs a [`PositionedParticle`] from a position, with defaults for
+/// everything not set explicitly.
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleBuilder {
+    position: Vec2,
+    /// Rotation around the z axis, in radians.
+    rotation: f32,
+    size: f32,
+    velocity: Velocity,
+    restitution: f32,
+    /// Fixed in place instead of simulated.
+    pinned: bool,
+}
+
+impl ParticleBuilder {
+    pub fn at(position: Vec2) -> Self {
+        Self {
+            position,
+            rotation: 0.0,
+            size: units::from_millimeters(4.0),
+            velocity: Velocity::zero(),
+            restitution: 1.0,
+            pinned: false,
+        }
+    }
+
+    /// Continues from a recorded particle, e.g. when undoing a deletion.
+    pub fn from_state(state: &ParticleState) -> Self {
+        Self::at(state.transform.translation.truncate())
+            .rotation(state.transform.rotation.to_euler(EulerRot::XYZ).2)
+            .size(state.size)
+            .velocity(state.velocity)
+            .pinned(state.pinned)
+    }
+
+    /// Edge length in world units, raised to [`sizing::MIN_PARTICLE_SIZE`].
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    pub fn velocity(mut self, velocity: Velocity) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn restitution(mut self, coefficient: f32) -> Self {
+        self.restitution = coefficient;
+        self
+    }
+
+    pub fn pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Launches the particle in a random direction, starting slightly away
+    /// from its position so particles spawned together do not overlap.
+    pub fn scattered(mut self, rng: &mut impl Rng) -> Self {
+        let angle = rng.gen_range(0.0..2. * std::f32::consts::PI);
+        let direction = Vec2::new(angle.sin(), angle.cos());
+        self.position += direction * SCATTER_DISTANCE;
+        self.velocity = Velocity::linear(direction * LAUNCH_SPEED);
+        self
+    }
+
+    pub fn build(self) -> Result<PositionedParticle, String> {
+        let size = sizing::validate_size(self.size)?;
+        if !self.position.is_finite() || !self.rotation.is_finite() {
+            return Err(format!("invalid particle position {}", self.position));
+        }
+        if !self.velocity.linvel.is_finite() || !self.velocity.angvel.is_finite() {
+            return Err(format!(
+                "invalid particle velocity {}",
+                self.velocity.linvel
+            ));
+        }
+        if !self.restitution.is_finite() || self.restitution < 0.0 {
+            return Err(format!("invalid restitution {}", self.restitution));
+        }
+
+        let transform = Transform::from_translation(self.position.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(self.rotation));
+        Ok(PositionedParticle {
+            particle: Particle { size },
+            rigid_body: if self.pinned {
+                RigidBody::Fixed
+            } else {
+                RigidBody::Dynamic
+            },
+            easing: pulse(size),
+            collider: sizing::collider(size),
+            restitution: Restitution::coefficient(self.restitution),
+            velocity: self.velocity,
+            mass_properties: ReadMassProperties::default(),
+            // Impacts are detected from collision events.
+            active_events: ActiveEvents::COLLISION_EVENTS,
+            ccd: Ccd::disabled(),
+            sleeping: Sleeping::default(),
+            trail: Trail::default(),
+            age: Age::default(),
+            sprite: SpriteBundle {
+                transform,
+                sprite: Sprite {
+                    color: PARTICLE_COLOR,
+                    custom_size: Some(Vec2::new(size, size)),
+                    ..default()
+                },
+                ..default()
+            },
+        })
+    }
+}
+
+/// The glow every particle of edge length `size` pulses with.
+pub fn pulse(size: f32) -> EasingComponent<Sprite> {
+    Sprite {
+        color: PARTICLE_COLOR,
+        custom_size: Some(Vec2::new(size, size)),
+        ..default()
+    }
+    .ease_to(
+        Sprite {
+            color: Color::rgb(0.9, 0.9, 1.2),
+            custom_size: Some(Vec2::new(size * 1.2, size * 1.2)),
+            ..Default::default()
+        },
+        EaseFunction::SineInOut,
+        EasingType::PingPong {
+            duration: Duration::from_millis(500),
+            pause: None,
+        },
+    )
+}
+
+fn add_squares(
+    mut particle_counter: ResMut<ParticleCount>,
+    mut rng: ResMut<SimulationRng>,
+    mut commands: Commands,
+) {
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            ..default()
+        },
+        BloomSettings {
+            intensity: BLOOM_INTENSITY,
+            ..default()
+        },
+        MainCamera,
+    ));
+    spawn_default_scene(&mut commands, &mut rng);
+    particle_counter.0 += 1;
+}
+
+/// The arena with a single particle, used at startup and when resetting
+/// without a loaded scene.
+pub fn spawn_default_scene(commands: &mut Commands, rng: &mut SimulationRng) {
+    let first = ParticleBuilder::at(Vec2::new(0.0, units::from_millimeters(200.0)))
+        .size(units::from_millimeters(32.0))
+        .scattered(&mut rng.rng)
+        .build()
+        .expect("the initial particle is valid");
+    commands.spawn(first);
+    spawn_arena(commands, Vec2::ZERO);
+}
+
+/// Spawns the floor, ceiling and side walls around `origin`.
+pub fn spawn_arena(commands: &mut Commands, origin: Vec2) -> Vec<Entity> {
+    // Half extents and positions in meters.
+    let walls = [
+        /* Create the ground. */
+        (Vec2::new(0.5, 0.05), Vec2::new(0.0, -0.3)),
+        (Vec2::new(0.5, 0.05), Vec2::new(0.0, 0.3)),
+        // create walls
+        (Vec2::new(0.05, 0.5), Vec2::new(-0.25, 0.0)),
+        (Vec2::new(0.05, 0.5), Vec2::new(0.25, 0.0)),
+    ];
+    walls
+        .into_iter()
+        .map(|(half_extents, position)| {
+            spawn_wall(
+                commands,
+                half_extents * units::WORLD_UNITS_PER_METER,
+                origin + position * units::WORLD_UNITS_PER_METER,
+            )
+        })
+        .collect()
+}
+
+pub fn spawn_wall(commands: &mut Commands, half_extents: Vec2, position: Vec2) -> Entity {
+    commands
+        .spawn(Collider::cuboid(half_extents.x, half_extents.y))
+        .insert(TransformBundle::from(Transform::from_translation(
+            position.extend(0.0),
+        )))
+        .insert(Wall { half_extents })
+        .id()
+}
+
+/// Order of the per-frame work in `CoreStage::Update`. Physics steps in
+/// rapier's stages after Update, with a fixed timestep when deterministic.
+#[derive(SystemLabel, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Pipeline {
+    /// Turning device input into actions, tool use and state changes.
+    Input,
+    /// Creating particles from spawn requests.
+    Spawning,
+    /// Camera motion and overlays drawn from the current state.
+    Visual,
+    /// egui windows. Commands they send are handled on the next frame.
+    Ui,
+}
+
+/// Number of particles spawned per click or tap.
+#[derive(Resource)]
+pub struct Particles(pub i32);
+
+/// Request to spawn `count` particles at `position`, sent by the spawn tool
+/// (once per position of its [`Placement`]) and by replays.
+#[derive(Clone, Copy)]
+pub struct SpawnRequest {
+    pub position: Vec2,
+    pub count: i32,
+}
+
+/// A particle placed with exact properties by a tool, e.g. the lattice or
+/// paste, rather than scattered by a [`SpawnRequest`]. Sent so replays can
+/// record it.
+#[derive(Clone, Copy)]
+pub struct ParticlePlaced(pub ParticleBuilder);
+
+/// Spawns particles within `--max-particles` and the adaptive quality cap,
+/// keeping [`ParticleCount`] and the edit history up to date.
+#[derive(SystemParam)]
+pub struct ParticleSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    particle_counter: ResMut<'w, ParticleCount>,
+    history: ResMut<'w, EditHistory>,
+    placed: EventWriter<'w, 's, ParticlePlaced>,
+    cli: Option<Res<'w, Cli>>,
+    cap: Option<Res<'w, SpawnCap>>,
+}
+
+impl<'w, 's> ParticleSpawner<'w, 's> {
+    fn at_capacity(&self) -> bool {
+        let max_particles = self
+            .cli
+            .as_ref()
+            .and_then(|cli| cli.max_particles)
+            .unwrap_or(u32::MAX)
+            .min(self.cap.as_ref().and_then(|cap| cap.0).unwrap_or(u32::MAX));
+        self.particle_counter.0 >= max_particles
+    }
+
+    /// Spawns one particle unless the cap is reached or it is invalid, and
+    /// returns its state for the edit history.
+    fn spawn(&mut self, builder: ParticleBuilder) -> Option<ParticleState> {
+        if self.at_capacity() {
+            return None;
+        }
+        let particle = builder.build().ok()?;
+        let transform = particle.sprite.transform;
+        let velocity = particle.velocity;
+        let size = particle.particle.size;
+        let entity = self.commands.spawn(particle).id();
+        self.particle_counter.0 += 1;
+        Some(ParticleState {
+            entity,
+            transform,
+            velocity,
+            size,
+            pinned: builder.pinned,
+        })
+    }
+
+    /// Spawns particles with exact properties as one undoable action and
+    /// records them for replays. Returns the spawned particles; those past
+    /// the cap or invalid are skipped.
+    pub fn place(&mut self, builders: impl IntoIterator<Item = ParticleBuilder>) -> Vec<Entity> {
+        let mut states = Vec::new();
+        for builder in builders {
+            if let Some(state) = self.spawn(builder) {
+                self.placed.send(ParticlePlaced(builder));
+                states.push(state);
+            }
+        }
+        let entities = states.iter().map(|state| state.entity).collect();
+        if !states.is_empty() {
+            self.history.record(EditAction::Spawn(states));
+        }
+        entities
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn mouse_button_events(
+    particles: Res<Particles>,
+    tool: Res<Tool>,
+    placement: Res<Placement>,
+    actions: ActionInput,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    mut spawn_requests: EventWriter<SpawnRequest>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+
+    if *tool != Tool::Spawn
+        || !actions.pressed(Action::UseTool)
+        || pointer_over_ui(&mut egui_context)
+    {
+        return;
+    }
+    if let Some(world_position) = cursor_world_position(window, camera, camera_transform) {
+        spawn_requests.send_batch(placement.requests(world_position, particles.0));
+    }
+}
+
+fn spawn_particles(
+    mut spawner: ParticleSpawner,
+    mut spawn_requests: EventReader<SpawnRequest>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    for request in spawn_requests.iter() {
+        for _ in 0..request.count {
+            if spawner.at_capacity() {
+                return;
+            }
+            let size = units::from_millimeters(rng.rng.gen_range(SPAWN_SIZES_MM) as f32);
+            let builder = ParticleBuilder::at(request.position)
+                .size(size)
+                .scattered(&mut rng.rng);
+            // Replays record the request itself, so this is not placed.
+            let Some(state) = spawner.spawn(builder) else {
+                return;
+            };
+            spawner.history.record_spawn(state);
+        }
+    }
+}
+
+fn mouse_scroll_events(
+    mut particles: ResMut<Particles>,
+    mut indicator: ResMut<SpawnCountIndicator>,
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    actions: ActionInput,
+    mut scroll_event: EventReader<MouseWheel>,
+) {
+    let before = particles.0;
+    if actions.just_pressed(Action::MoreParticles) {
+        particles.0 += 1;
+    }
+    if actions.just_pressed(Action::FewerParticles) {
+        particles.0 -= 1;
+    }
+    if modifier_pressed(&keys) {
+        for ev in scroll_event.iter() {
+            particles.0 += if ev.y > 0.0 { 1 } else { -1 };
+        }
+    } else {
+        scroll_event.clear();
+    }
+    if particles.0 != before {
+        indicator.show(&time);
+    }
+}
+
+#[derive(Resource)]
+pub struct ParticleCount(pub u32);
+
+fn show_particle_count(particles: Res<ParticleCount>, mut log: ResMut<ConsoleLog>) {
+    if particles.is_changed() {
+        log.category(LogCategory::Edit)
+            .debug(format!("Particle count: {}", particles.0));
+    }
+}
+
+/// The physics world and everything operating on whole scenes: seeding,
+/// pausing, neighbor lookups, impacts, rewind, undo, scene files,
+/// checkpoints, recording, scripts and statistics.
+pub struct SimulationPlugin {
+    pub seed: Option<u64>,
+    pub deterministic: bool,
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(SimulationRngPlugin { seed: self.seed })
+            .add_plugin(ConsolePlugin)
+            .add_plugin(SimulationControlPlugin {
+                deterministic: self.deterministic,
+            })
+            .add_plugin(SpatialHashPlugin)
+            .add_plugin(ImpactsPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(ExportPlugin)
+            .add_plugin(ContactGraphPlugin)
+            .add_plugin(SnapshotPlugin)
+            .add_plugin(RewindPlugin)
+            .add_plugin(HistoryPlugin)
+            .add_plugin(ScenePlugin)
+            .add_plugin(AutosavePlugin)
+            .add_plugin(CheckpointPlugin)
+            .add_plugin(ScenariosPlugin)
+            .add_plugin(ReplayPlugin)
+            .add_plugin(ScriptingPlugin)
+            .add_plugin(SolverPlugin);
+        #[cfg(feature = "metrics")]
+        app.add_plugin(metrics::MetricsPlugin);
+        #[cfg(feature = "remote")]
+        app.add_plugin(remote::RemotePlugin);
+        app.add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(
+            units::WORLD_UNITS_PER_METER,
+        ));
+        // .add_plugin(RapierDebugRenderPlugin::default())
+    }
+}
+
+/// The camera, the arena, particle spawning and aging.
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ParticleCount(0))
+            .add_event::<SpawnRequest>()
+            .add_event::<ParticlePlaced>()
+            .add_plugin(AgePlugin)
+            .add_startup_system(add_squares)
+            .add_system(
+                spawn_particles
+                    .label(Pipeline::Spawning)
+                    .after(Pipeline::Input),
+            )
+            .add_system(show_particle_count);
+    }
+}
+
+/// Keyboard, mouse, gamepad and touch controls, the tools they drive and
+/// hover tooltips.
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(InputMappingPlugin)
+            .add_plugin(CameraControlsPlugin)
+            .add_plugin(GamepadControlsPlugin)
+            .add_plugin(TouchPlugin)
+            .add_plugin(SelectionPlugin)
+            .add_plugin(ContextMenuPlugin)
+            .add_plugin(ClipboardPlugin)
+            .add_plugin(GroupsPlugin)
+            .add_plugin(ToolsPlugin)
+            .add_plugin(PlacementPlugin)
+            .add_plugin(SpawnPreviewPlugin)
+            .add_plugin(LatticePlugin)
+            .add_plugin(ChargePlugin)
+            .add_plugin(AnnotationsPlugin)
+            .add_plugin(TooltipPlugin)
+            .add_system(mouse_button_events.label(Pipeline::Input))
+            .add_system(mouse_scroll_events.label(Pipeline::Input));
+    }
+}
+
+/// Start menu, inspector, scene tabs, visual overlays and effects, HUD,
+/// adaptive quality and sound.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(WorldInspectorPlugin)
+            .add_plugin(EasingsPlugin)
+            .add_plugin(TrailsPlugin)
+            .add_plugin(SplitScreenPlugin)
+            .add_plugin(RulerPlugin)
+            .add_plugin(QualityPlugin)
+            .add_plugin(AudioFeedbackPlugin)
+            .add_plugin(EffectsPlugin)
+            .add_plugin(HudPlugin)
+            .add_plugin(TabsPlugin)
+            .add_plugin(MenuPlugin);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::cli::Cli;
+use crate::input::{Action, ActionInput, Keybindings};
+use crate::scenarios::ScenarioLibrary;
+use crate::scene::SceneCommand;
+use crate::sim_control::SimulationControl;
+use crate::trails::TrailSettings;
+use crate::{Particles, Pipeline};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+
+/// Top-level state of the windowed app. `Running` and `Paused` follow
+/// [`SimulationControl::paused`], so the existing pause controls keep working.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AppState {
+    /// The start menu is shown and physics is held.
+    Menu,
+    Running,
+    Paused,
+}
+
+/// Choices made in the start menu.
+#[derive(Resource, Default)]
+struct MenuSelection {
+    /// Index into the scenario library, `None` for the default arena.
+    scenario: Option<usize>,
+    /// Whether the simulation was started before, so the menu can resume it.
+    started: bool,
+}
+
+/// Scripted and scenario runs start right away instead of waiting in the
+/// menu.
+fn skip_menu(
+    cli: Option<Res<Cli>>,
+    mut state: ResMut<State<AppState>>,
+    mut selection: ResMut<MenuSelection>,
+) {
+    if cli.is_some_and(|cli| cli.scenario.is_some() || cli.script.is_some()) {
+        selection.started = true;
+        let _ = state.set(AppState::Running);
+    }
+}
+
+fn open_menu(
+    actions: ActionInput,
+    selection: Res<MenuSelection>,
+    mut state: ResMut<State<AppState>>,
+    mut control: ResMut<SimulationControl>,
+) {
+    if !actions.just_pressed(Action::OpenMenu) {
+        return;
+    }
+    match state.current() {
+        AppState::Menu if selection.started => {
+            control.paused = false;
+            let _ = state.set(AppState::Running);
+        }
+        AppState::Menu => {}
+        AppState::Running | AppState::Paused => {
+            let _ = state.set(AppState::Menu);
+        }
+    }
+}
+
+fn sync_pause_state(mut state: ResMut<State<AppState>>, mut control: ResMut<SimulationControl>) {
+    match state.current() {
+        AppState::Menu => {
+            if !control.paused {
+                control.paused = true;
+            }
+        }
+        AppState::Running if control.paused => {
+            let _ = state.set(AppState::Paused);
+        }
+        AppState::Paused if !control.paused => {
+            let _ = state.set(AppState::Running);
+        }
+        AppState::Running | AppState::Paused => {}
+    }
+}
+
+/// Leaves the menu, loading the chosen scenario if it is a fresh start.
+fn start(
+    selection: &mut MenuSelection,
+    library: &ScenarioLibrary,
+    state: &mut State<AppState>,
+    control: &mut SimulationControl,
+    scene_commands: &mut EventWriter<SceneCommand>,
+) {
+    if !selection.started {
+        if let Some(scenario) = selection.scenario.and_then(|i| library.scenarios.get(i)) {
+            scene_commands.send(SceneCommand::Load(scenario.path.clone()));
+        }
+        selection.started = true;
+    }
+    control.paused = false;
+    let _ = state.set(AppState::Running);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<AppState>>,
+    mut selection: ResMut<MenuSelection>,
+    library: Res<ScenarioLibrary>,
+    bindings: Res<Keybindings>,
+    mut control: ResMut<SimulationControl>,
+    mut particles: ResMut<Particles>,
+    mut trails: ResMut<TrailSettings>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    if *state.current() != AppState::Menu {
+        return;
+    }
+    let ctx = egui_context.ctx_mut();
+    // Dims the scene and keeps clicks from reaching the tools.
+    egui::CentralPanel::default()
+        .frame(egui::Frame::none().fill(egui::Color32::from_black_alpha(160)))
+        .show(ctx, |_| {});
+
+    egui::Window::new("physicsboi")
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.add_enabled_ui(!selection.started, |ui| {
+                ui.heading("Scenario");
+                ui.radio_value(&mut selection.scenario, None, "Empty arena");
+                for (index, scenario) in library.scenarios.iter().enumerate() {
+                    ui.radio_value(&mut selection.scenario, Some(index), &scenario.name);
+                }
+            });
+            ui.separator();
+            ui.heading("Settings");
+            ui.add(egui::Slider::new(&mut particles.0, 1..=50).text("Particles per click"));
+            ui.add(
+                egui::Slider::new(&mut control.time_scale, 0.1..=10.0)
+                    .logarithmic(true)
+                    .text("Time scale"),
+            );
+            ui.checkbox(&mut control.deterministic, "Fixed timestep");
+            ui.checkbox(&mut trails.enabled, "Trails");
+            ui.separator();
+            let label = if selection.started { "Resume" } else { "Start" };
+            ui.horizontal(|ui| {
+                if ui
+                    .button(label)
+                    .on_hover_text(bindings.label(Action::OpenMenu))
+                    .clicked()
+                {
+                    start(
+                        &mut selection,
+                        &library,
+                        &mut state,
+                        &mut control,
+                        &mut scene_commands,
+                    );
+                }
+                if selection.started
+                    && ui
+                        .button("Reset")
+                        .on_hover_text(bindings.label(Action::Reset))
+                        .clicked()
+                {
+                    scene_commands.send(SceneCommand::Reset);
+                    start(
+                        &mut selection,
+                        &library,
+                        &mut state,
+                        &mut control,
+                        &mut scene_commands,
+                    );
+                }
+            });
+        });
+}
+
+/// Start menu to pick a scenario and initial settings, and the app states
+/// around it.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(AppState::Menu)
+            .init_resource::<MenuSelection>()
+            .add_startup_system(skip_menu)
+            .add_system(open_menu.label(Pipeline::Input))
+            .add_system(sync_pause_state.label(Pipeline::Input).after(open_menu))
+            .add_system(menu_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
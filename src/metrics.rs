@@ -0,0 +1,108 @@
+use crate::cli::Cli;
+use crate::console::ConsoleLog;
+use crate::sim_control::SimClock;
+use crate::stats::SimStats;
+use crate::ParticleCount;
+use bevy::prelude::*;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+/// Latest metrics in the Prometheus text format, shared with the server
+/// thread. Only exists while the server runs.
+#[derive(Resource, Clone, Default)]
+struct MetricsPage(Arc<Mutex<String>>);
+
+fn respond(mut stream: TcpStream, page: &MetricsPage) {
+    // Every request gets the metrics, so the request itself is not parsed.
+    let mut request = [0; 1024];
+    let _ = stream.read(&mut request);
+    let body = page.0.lock().unwrap().clone();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+}
+
+fn start_server(cli: Option<Res<Cli>>, mut log: ResMut<ConsoleLog>, mut commands: Commands) {
+    let Some(address) = cli.and_then(|cli| cli.metrics.clone()) else {
+        return;
+    };
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log.warn(format!("Could not serve metrics on {address}: {e}"));
+            return;
+        }
+    };
+    log.info(format!("Serving metrics on http://{address}/metrics"));
+    let page = MetricsPage::default();
+    commands.insert_resource(page.clone());
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            respond(stream, &page);
+        }
+    });
+}
+
+fn gauge(page: &mut String, name: &str, help: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(page, "# HELP physicsboi_{name} {help}");
+    let _ = writeln!(page, "# TYPE physicsboi_{name} gauge");
+    let _ = writeln!(page, "physicsboi_{name} {value}");
+}
+
+fn update_metrics(
+    page: Option<Res<MetricsPage>>,
+    time: Res<Time>,
+    clock: Res<SimClock>,
+    particles: Res<ParticleCount>,
+    stats: Res<SimStats>,
+) {
+    let Some(page) = page else {
+        return;
+    };
+    let current = &stats.current;
+    let mut text = String::new();
+    gauge(&mut text, "particles", "Live particles.", particles.0);
+    gauge(
+        &mut text,
+        "kinetic_energy_joules",
+        "Total translational kinetic energy.",
+        current.kinetic_energy,
+    );
+    gauge(
+        &mut text,
+        "mean_speed_meters_per_second",
+        "Mean particle speed.",
+        current.mean_speed,
+    );
+    gauge(
+        &mut text,
+        "max_speed_meters_per_second",
+        "Highest particle speed.",
+        current.max_speed,
+    );
+    let _ = writeln!(text, "# HELP physicsboi_steps_total Physics steps taken.");
+    let _ = writeln!(text, "# TYPE physicsboi_steps_total counter");
+    let _ = writeln!(text, "physicsboi_steps_total {}", clock.steps);
+    gauge(
+        &mut text,
+        "frame_seconds",
+        "Wall clock duration of the last frame.",
+        time.delta_seconds(),
+    );
+    *page.0.lock().unwrap() = text;
+}
+
+/// Serves simulation metrics for Prometheus on the address given with
+/// `--metrics`, to monitor long headless runs.
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(start_server)
+            .add_system_to_stage(CoreStage::Last, update_metrics);
+    }
+}
@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::egui;
+
+/// Painter drawing behind egui windows but on top of the rendered scene,
+/// clipped to the viewport of `camera`.
+pub fn overlay_painter(ctx: &egui::Context, id: &'static str, camera: &Camera) -> egui::Painter {
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new(id),
+    ));
+    match camera.logical_viewport_rect() {
+        Some((min, max)) => painter.with_clip_rect(egui::Rect::from_min_max(
+            egui::pos2(min.x, min.y),
+            egui::pos2(max.x, max.y),
+        )),
+        None => painter,
+    }
+}
+
+/// Projects a world position to egui screen coordinates (origin at the top left).
+pub fn world_to_screen(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    window: &Window,
+    point: Vec2,
+) -> Option<egui::Pos2> {
+    camera
+        .world_to_viewport(camera_transform, point.extend(0.0))
+        .map(|p| egui::pos2(p.x, window.height() - p.y))
+}
@@ -0,0 +1,186 @@
+use crate::camera::MainCamera;
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::tools::Tool;
+use crate::units::from_millimeters;
+use crate::{Pipeline, SpawnRequest};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+
+/// How a spawn is repeated around the clicked position.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArrayMode {
+    Single,
+    /// `columns` by `rows` copies, centered on the clicked position.
+    Grid,
+    /// `count` copies evenly spaced on a circle around the clicked position.
+    Radial,
+}
+
+/// Modifiers applied to every spawn of the spawn tool.
+#[derive(Resource, Clone, PartialEq)]
+pub struct Placement {
+    /// Also spawn mirrored across the vertical line at `mirror_x`.
+    pub mirror: bool,
+    pub mirror_x: f32,
+    pub array: ArrayMode,
+    pub columns: u32,
+    pub rows: u32,
+    /// Distance between grid copies, in world units.
+    pub spacing: f32,
+    pub count: u32,
+    pub radius: f32,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Self {
+            mirror: false,
+            mirror_x: 0.0,
+            array: ArrayMode::Single,
+            columns: 3,
+            rows: 3,
+            spacing: from_millimeters(40.0),
+            count: 6,
+            radius: from_millimeters(60.0),
+        }
+    }
+}
+
+impl Placement {
+    /// All positions a spawn at `position` expands to.
+    pub fn positions(&self, position: Vec2) -> Vec<Vec2> {
+        let mut positions = match self.array {
+            ArrayMode::Single => vec![position],
+            ArrayMode::Grid => {
+                let size = Vec2::new(self.columns as f32 - 1.0, self.rows as f32 - 1.0);
+                let corner = position - size * self.spacing / 2.0;
+                (0..self.rows)
+                    .flat_map(|row| (0..self.columns).map(move |column| (column, row)))
+                    .map(|(column, row)| {
+                        corner + Vec2::new(column as f32, row as f32) * self.spacing
+                    })
+                    .collect()
+            }
+            ArrayMode::Radial => (0..self.count)
+                .map(|i| {
+                    let angle = i as f32 / self.count as f32 * std::f32::consts::TAU;
+                    position + Vec2::new(angle.cos(), angle.sin()) * self.radius
+                })
+                .collect(),
+        };
+        if self.mirror {
+            let mirrored: Vec<_> = positions
+                .iter()
+                .map(|p| Vec2::new(2.0 * self.mirror_x - p.x, p.y))
+                .collect();
+            positions.extend(mirrored);
+        }
+        positions
+    }
+
+    /// Spawn requests for a spawn of `count` particles at `position`.
+    pub fn requests(&self, position: Vec2, count: i32) -> impl Iterator<Item = SpawnRequest> {
+        self.positions(position)
+            .into_iter()
+            .map(move |position| SpawnRequest { position, count })
+    }
+}
+
+fn placement_ui(
+    tool: Res<Tool>,
+    mut egui_context: ResMut<EguiContext>,
+    mut placement: ResMut<Placement>,
+) {
+    if *tool != Tool::Spawn {
+        return;
+    }
+    let mut edited = placement.clone();
+    egui::Window::new("Placement").show(egui_context.ctx_mut(), |ui| {
+        ui.checkbox(&mut edited.mirror, "Mirror");
+        ui.add_enabled(
+            edited.mirror,
+            egui::DragValue::new(&mut edited.mirror_x).prefix("Axis x: "),
+        );
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut edited.array, ArrayMode::Single, "Single");
+            ui.radio_value(&mut edited.array, ArrayMode::Grid, "Grid");
+            ui.radio_value(&mut edited.array, ArrayMode::Radial, "Radial");
+        });
+        match edited.array {
+            ArrayMode::Single => {}
+            ArrayMode::Grid => {
+                ui.add(egui::Slider::new(&mut edited.columns, 1..=20).text("Columns"));
+                ui.add(egui::Slider::new(&mut edited.rows, 1..=20).text("Rows"));
+                ui.add(
+                    egui::Slider::new(
+                        &mut edited.spacing,
+                        from_millimeters(5.0)..=from_millimeters(200.0),
+                    )
+                    .text("Spacing"),
+                );
+            }
+            ArrayMode::Radial => {
+                ui.add(egui::Slider::new(&mut edited.count, 2..=36).text("Copies"));
+                ui.add(
+                    egui::Slider::new(
+                        &mut edited.radius,
+                        from_millimeters(5.0)..=from_millimeters(500.0),
+                    )
+                    .text("Radius"),
+                );
+            }
+        }
+    });
+    if edited != *placement {
+        *placement = edited;
+    }
+}
+
+fn draw_mirror_axis(
+    tool: Res<Tool>,
+    placement: Res<Placement>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    if *tool != Tool::Spawn || !placement.mirror {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let Some(axis) = world_to_screen(
+        camera,
+        camera_transform,
+        window,
+        Vec2::new(placement.mirror_x, 0.0),
+    ) else {
+        return;
+    };
+    let painter = overlay_painter(egui_context.ctx_mut(), "mirror_axis", camera);
+    let clip = painter.clip_rect();
+    painter.vline(
+        axis.x,
+        clip.y_range(),
+        egui::Stroke::new(
+            1.0,
+            egui::Color32::from_rgba_unmultiplied(120, 200, 255, 120),
+        ),
+    );
+}
+
+/// Mirror and array modifiers for the spawn tool, for building symmetric and
+/// regular arrangements in one click.
+pub struct PlacementPlugin;
+
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Placement>()
+            .add_system(placement_ui.label(Pipeline::Ui).after(Pipeline::Visual))
+            .add_system(
+                draw_mirror_axis
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            );
+    }
+}
@@ -0,0 +1,323 @@
+use crate::audio::AudioSettings;
+use crate::console::ConsoleLog;
+use crate::effects::EffectSettings;
+use crate::solver::SolverSettings;
+use crate::trails::TrailSettings;
+use crate::{ParticleCount, Pipeline, BLOOM_INTENSITY};
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Number of frames the FPS is averaged over.
+const AVERAGE_FRAMES: usize = 60;
+/// Seconds between quality changes, so each change shows up in the average
+/// before the next one is decided.
+const ADJUST_INTERVAL: f32 = 2.0;
+/// Quality is only raised again once the average exceeds the target by this
+/// factor, to avoid flip-flopping around it.
+const RESTORE_HEADROOM: f32 = 1.2;
+/// Trail length while trails are shortened.
+const REDUCED_TRAIL_LENGTH: usize = 8;
+
+/// Named groups of rendering, effect and solver settings, picked from one
+/// dropdown.
+#[derive(Reflect, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Profile {
+    /// Everything on and more solver iterations for stable stacks.
+    Quality,
+    #[default]
+    Balanced,
+    /// No antialiasing, bloom, trails, sparks or collision sounds and fewer
+    /// solver iterations, for the largest particle counts.
+    MaxParticles,
+}
+
+impl Profile {
+    const ALL: [(Profile, &'static str); 3] = [
+        (Profile::Quality, "Quality"),
+        (Profile::Balanced, "Balanced"),
+        (Profile::MaxParticles, "Max particles"),
+    ];
+
+    fn label(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(profile, _)| *profile == self)
+            .map_or("", |(_, label)| label)
+    }
+
+    fn msaa_samples(self) -> u32 {
+        match self {
+            Profile::Quality | Profile::Balanced => 4,
+            Profile::MaxParticles => 1,
+        }
+    }
+
+    /// Trails are switched on or off, or left as they are.
+    fn trails(self) -> Option<bool> {
+        match self {
+            Profile::Quality => Some(true),
+            Profile::Balanced => None,
+            Profile::MaxParticles => Some(false),
+        }
+    }
+
+    fn bloom(self) -> bool {
+        self != Profile::MaxParticles
+    }
+
+    /// Velocity, friction and stabilization iterations of the solver.
+    fn solver_iterations(self) -> (usize, usize, usize) {
+        match self {
+            Profile::Quality => (8, 8, 2),
+            Profile::Balanced => (4, 8, 1),
+            Profile::MaxParticles => (2, 4, 1),
+        }
+    }
+}
+
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct QualitySettings {
+    pub adaptive: bool,
+    pub target_fps: f32,
+    /// Whether the last reduction stops spawning beyond the current count.
+    pub cap_spawning: bool,
+    #[serde(default)]
+    pub profile: Profile,
+}
+
+impl Default for QualitySettings {
+    fn default() -> Self {
+        Self {
+            adaptive: true,
+            target_fps: 45.0,
+            cap_spawning: false,
+            profile: default(),
+        }
+    }
+}
+
+/// Visual reductions currently applied, from none to most drastic.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum QualityLevel {
+    #[default]
+    Full,
+    NoBloom,
+    ShortTrails,
+    CappedSpawning,
+}
+
+impl QualityLevel {
+    fn lower(self, settings: &QualitySettings) -> Option<Self> {
+        match self {
+            Self::Full => Some(Self::NoBloom),
+            Self::NoBloom => Some(Self::ShortTrails),
+            Self::ShortTrails if settings.cap_spawning => Some(Self::CappedSpawning),
+            Self::ShortTrails | Self::CappedSpawning => None,
+        }
+    }
+
+    fn higher(self) -> Option<Self> {
+        match self {
+            Self::Full => None,
+            Self::NoBloom => Some(Self::Full),
+            Self::ShortTrails => Some(Self::NoBloom),
+            Self::CappedSpawning => Some(Self::ShortTrails),
+        }
+    }
+
+    /// Number of trail points to keep given the configured length.
+    pub fn trail_length(self, length: usize) -> usize {
+        if self >= Self::ShortTrails {
+            length.min(REDUCED_TRAIL_LENGTH)
+        } else {
+            length
+        }
+    }
+}
+
+/// Particle count spawning stops at while quality is at its lowest.
+#[derive(Resource, Default)]
+pub struct SpawnCap(pub Option<u32>);
+
+#[derive(Default)]
+struct FrameWindow {
+    frame_times: VecDeque<f32>,
+    since_change: f32,
+}
+
+impl FrameWindow {
+    fn average_fps(&self) -> Option<f32> {
+        if self.frame_times.len() < AVERAGE_FRAMES {
+            return None;
+        }
+        let total: f32 = self.frame_times.iter().sum();
+        Some(self.frame_times.len() as f32 / total)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn adjust_quality(
+    time: Res<Time>,
+    settings: Res<QualitySettings>,
+    particle_count: Res<ParticleCount>,
+    mut window: Local<FrameWindow>,
+    mut level: ResMut<QualityLevel>,
+    mut cap: ResMut<SpawnCap>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    if !settings.adaptive {
+        if *level != QualityLevel::Full {
+            *level = QualityLevel::Full;
+            cap.0 = None;
+        }
+        return;
+    }
+    window.frame_times.push_back(time.delta_seconds());
+    while window.frame_times.len() > AVERAGE_FRAMES {
+        window.frame_times.pop_front();
+    }
+    window.since_change += time.delta_seconds();
+    let Some(fps) = window.average_fps() else {
+        return;
+    };
+    if window.since_change < ADJUST_INTERVAL {
+        return;
+    }
+
+    let next = if fps < settings.target_fps {
+        level.lower(&settings)
+    } else if fps > settings.target_fps * RESTORE_HEADROOM {
+        level.higher()
+    } else {
+        None
+    };
+    let Some(next) = next else {
+        return;
+    };
+    *level = next;
+    cap.0 = (next == QualityLevel::CappedSpawning).then_some(particle_count.0);
+    window.since_change = 0.0;
+    log.info(format!("Quality set to {next:?} at {fps:.0} FPS"));
+}
+
+/// Applies the profile's antialiasing and solver iterations, and its trails,
+/// sparks and collision sounds when the profile is switched. Those stay as
+/// saved on startup.
+#[allow(clippy::too_many_arguments)]
+fn apply_profile(
+    settings: Res<QualitySettings>,
+    mut applied: Local<Option<Profile>>,
+    mut msaa: ResMut<Msaa>,
+    mut solver: ResMut<SolverSettings>,
+    mut trails: ResMut<TrailSettings>,
+    mut effects: ResMut<EffectSettings>,
+    mut audio: ResMut<AudioSettings>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let profile = settings.profile;
+    if *applied == Some(profile) {
+        return;
+    }
+    if applied.is_some() {
+        let full = profile != Profile::MaxParticles;
+        if let Some(enabled) = profile.trails() {
+            trails.enabled = enabled;
+        }
+        effects.sparks = full;
+        audio.collisions = full;
+        log.info(format!("Switched to the {} profile", profile.label()));
+    }
+    *applied = Some(profile);
+    msaa.samples = profile.msaa_samples();
+    (
+        solver.velocity_iterations,
+        solver.friction_iterations,
+        solver.stabilization_iterations,
+    ) = profile.solver_iterations();
+}
+
+fn apply_bloom(
+    mut commands: Commands,
+    level: Res<QualityLevel>,
+    settings: Res<QualitySettings>,
+    cameras: Query<(Entity, Option<&BloomSettings>), With<Camera2d>>,
+) {
+    let bloom = settings.profile.bloom() && *level < QualityLevel::NoBloom;
+    for (camera, settings) in cameras.iter() {
+        match (bloom, settings.is_some()) {
+            (true, false) => {
+                commands.entity(camera).insert(BloomSettings {
+                    intensity: BLOOM_INTENSITY,
+                    ..default()
+                });
+            }
+            (false, true) => {
+                commands.entity(camera).remove::<BloomSettings>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn quality_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut settings: ResMut<QualitySettings>,
+    level: Res<QualityLevel>,
+    cap: Res<SpawnCap>,
+) {
+    egui::Window::new("Quality")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::ComboBox::from_label("Profile")
+                .selected_text(settings.profile.label())
+                .show_ui(ui, |ui| {
+                    for (profile, label) in Profile::ALL {
+                        ui.selectable_value(&mut settings.profile, profile, label);
+                    }
+                });
+            ui.separator();
+            ui.checkbox(&mut settings.adaptive, "Adapt to frame rate");
+            ui.add_enabled(
+                settings.adaptive,
+                egui::Slider::new(&mut settings.target_fps, 20.0..=144.0).text("Target FPS"),
+            );
+            ui.add_enabled(
+                settings.adaptive,
+                egui::Checkbox::new(&mut settings.cap_spawning, "Cap spawning as a last resort"),
+            );
+            ui.label(format!("Current: {:?}", *level));
+            if let Some(limit) = cap.0 {
+                ui.label(format!("Spawning capped at {limit} particles"));
+            }
+        });
+}
+
+/// Quality profiles, and lowering visual quality step by step while the frame
+/// rate stays below the target until there is headroom again.
+pub struct QualityPlugin;
+
+impl Plugin for QualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QualitySettings>()
+            .register_type::<QualitySettings>()
+            .init_resource::<QualityLevel>()
+            .init_resource::<SpawnCap>()
+            .add_system(adjust_quality)
+            .add_system(
+                apply_profile
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            )
+            .add_system(
+                apply_bloom
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning)
+                    .after(adjust_quality),
+            )
+            .add_system(quality_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
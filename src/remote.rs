@@ -0,0 +1,173 @@
+use crate::cli::Cli;
+use crate::console::{ConsoleLog, LogCategory};
+use crate::scene::SceneCommand;
+use crate::sim_control::{SimClock, SimulationControl};
+use crate::stats::SimStats;
+use crate::{ParticleCount, Pipeline, SpawnRequest};
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use tungstenite::Message;
+
+/// A JSON command sent by a remote client, e.g.
+/// `{"command": "spawn", "x": 0, "y": 100, "count": 5}`.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum RemoteCommand {
+    Spawn {
+        x: f32,
+        y: f32,
+        #[serde(default = "one")]
+        count: i32,
+    },
+    Pause,
+    Resume,
+    Stats,
+    SaveScene {
+        path: PathBuf,
+    },
+    LoadScene {
+        path: PathBuf,
+    },
+}
+
+fn one() -> i32 {
+    1
+}
+
+/// A command together with where to send its reply.
+type Request = (RemoteCommand, Sender<Value>);
+
+/// Commands received by the connection threads, drained every frame.
+#[derive(Resource)]
+struct RemoteRequests(Mutex<Receiver<Request>>);
+
+/// Answers each text message of one client until it disconnects.
+fn serve_client(stream: TcpStream, requests: Sender<Request>) {
+    let Ok(mut socket) = tungstenite::accept(stream) else {
+        return;
+    };
+    while let Ok(message) = socket.read_message() {
+        let reply = match message {
+            Message::Text(text) => match serde_json::from_str::<RemoteCommand>(&text) {
+                Ok(command) => {
+                    let (reply_sender, reply) = channel();
+                    if requests.send((command, reply_sender)).is_err() {
+                        return;
+                    }
+                    reply
+                        .recv()
+                        .unwrap_or_else(|_| json!({"error": "app closed"}))
+                }
+                Err(e) => json!({"error": e.to_string()}),
+            },
+            Message::Close(_) => return,
+            _ => continue,
+        };
+        if socket
+            .write_message(Message::Text(reply.to_string()))
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn start_server(cli: Option<Res<Cli>>, mut log: ResMut<ConsoleLog>, mut commands: Commands) {
+    let Some(address) = cli.and_then(|cli| cli.remote.clone()) else {
+        return;
+    };
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log.warn(format!(
+                "Could not listen for remote control on {address}: {e}"
+            ));
+            return;
+        }
+    };
+    log.info(format!("Listening for remote control on ws://{address}"));
+    let (sender, receiver) = channel();
+    commands.insert_resource(RemoteRequests(Mutex::new(receiver)));
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            std::thread::spawn(move || serve_client(stream, sender));
+        }
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_requests(
+    requests: Option<Res<RemoteRequests>>,
+    clock: Res<SimClock>,
+    stats: Res<SimStats>,
+    particle_count: Res<ParticleCount>,
+    mut control: ResMut<SimulationControl>,
+    mut log: ResMut<ConsoleLog>,
+    mut spawn_requests: EventWriter<SpawnRequest>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    let Some(requests) = requests else {
+        return;
+    };
+    let requests = requests.0.lock().unwrap();
+    for (command, reply) in requests.try_iter() {
+        log.category(LogCategory::Remote)
+            .debug(format!("Remote command {command:?}"));
+        let answer = match command {
+            RemoteCommand::Spawn { x, y, count } => {
+                spawn_requests.send(SpawnRequest {
+                    position: Vec2::new(x, y),
+                    count,
+                });
+                json!({"ok": true})
+            }
+            RemoteCommand::Pause => {
+                control.paused = true;
+                json!({"ok": true})
+            }
+            RemoteCommand::Resume => {
+                control.paused = false;
+                json!({"ok": true})
+            }
+            RemoteCommand::Stats => {
+                let current = &stats.current;
+                json!({
+                    "particles": particle_count.0,
+                    "kinetic_energy": current.kinetic_energy,
+                    "mean_speed": current.mean_speed,
+                    "max_speed": current.max_speed,
+                    "step": clock.steps,
+                    "paused": control.paused,
+                })
+            }
+            // Scene files are written and read later in the frame, whose
+            // outcome shows up in the console.
+            RemoteCommand::SaveScene { path } => {
+                scene_commands.send(SceneCommand::Save(path));
+                json!({"ok": true})
+            }
+            RemoteCommand::LoadScene { path } => {
+                scene_commands.send(SceneCommand::Load(path));
+                json!({"ok": true})
+            }
+        };
+        let _ = reply.send(answer);
+    }
+}
+
+/// Lets external scripts and notebooks drive the simulation with JSON
+/// commands over a WebSocket given with `--remote`.
+pub struct RemotePlugin;
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(start_server)
+            .add_system(handle_requests.label(Pipeline::Input));
+    }
+}
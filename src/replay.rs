@@ -0,0 +1,323 @@
+use crate::console::{ConsoleLog, LogCategory};
+use crate::input::{Action, ActionInput, Keybindings};
+use crate::rng::SimulationRng;
+use crate::scene::{SceneCommand, SceneIo};
+use crate::sim_control::{SimClock, SimulationControl};
+use crate::{ParticleBuilder, ParticlePlaced, ParticleSpawner, Pipeline, SpawnRequest};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+/// Version 2 added particles placed by tools.
+const REPLAY_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+struct ReplayFile {
+    version: u32,
+    seed: u64,
+    /// Scene the recording started from.
+    scene: PathBuf,
+    spawns: Vec<RecordedSpawn>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedSpawn {
+    /// Physics step the spawn happened before.
+    step: u64,
+    position: Vec2,
+    count: i32,
+    /// Set for a single particle placed by a tool, spawned as recorded
+    /// instead of `count` scattered ones.
+    #[serde(default)]
+    placed: Option<PlacedParticle>,
+}
+
+/// Everything but the position of a [`ParticlePlaced`].
+#[derive(Serialize, Deserialize)]
+struct PlacedParticle {
+    rotation: f32,
+    size: f32,
+    linvel: Vec2,
+    angvel: f32,
+    restitution: f32,
+    pinned: bool,
+}
+
+impl RecordedSpawn {
+    fn placed(step: u64, builder: &ParticleBuilder) -> Self {
+        Self {
+            step,
+            position: builder.position,
+            count: 1,
+            placed: Some(PlacedParticle {
+                rotation: builder.rotation,
+                size: builder.size,
+                linvel: builder.velocity.linvel,
+                angvel: builder.velocity.angvel,
+                restitution: builder.restitution,
+                pinned: builder.pinned,
+            }),
+        }
+    }
+}
+
+#[derive(Default)]
+enum ReplayMode {
+    #[default]
+    Idle,
+    Recording(ReplayFile),
+    Playing(VecDeque<RecordedSpawn>),
+}
+
+#[derive(Resource)]
+pub struct Replay {
+    pub path: String,
+    mode: ReplayMode,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self {
+            path: "replay.ron".to_string(),
+            mode: ReplayMode::Idle,
+        }
+    }
+}
+
+enum ReplayCommand {
+    ToggleRecording,
+    Play,
+}
+
+/// Scene file stored next to a replay, holding its starting state.
+fn scene_path(replay: &str) -> PathBuf {
+    PathBuf::from(replay).with_extension("scene.ron")
+}
+
+fn read_replay(path: &PathBuf) -> Result<ReplayFile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let replay: ReplayFile = ron::from_str(&text).map_err(|e| e.to_string())?;
+    if replay.version > REPLAY_VERSION {
+        return Err(format!(
+            "replay version {} is newer than supported version {REPLAY_VERSION}",
+            replay.version
+        ));
+    }
+    Ok(replay)
+}
+
+fn write_replay(path: &PathBuf, replay: &ReplayFile) -> Result<(), String> {
+    let text = ron::ser::to_string_pretty(replay, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_replay_command(
+    command: ReplayCommand,
+    replay: &mut Replay,
+    rng: &mut SimulationRng,
+    control: &mut SimulationControl,
+    clock: &mut SimClock,
+    scene_commands: &mut EventWriter<SceneCommand>,
+    log: &mut ConsoleLog,
+) {
+    let path = PathBuf::from(&replay.path);
+    match (command, std::mem::take(&mut replay.mode)) {
+        (ReplayCommand::ToggleRecording, ReplayMode::Recording(file)) => {
+            match write_replay(&path, &file) {
+                Ok(()) => log.category(LogCategory::Replay).info(format!(
+                    "Saved replay with {} spawns to {}",
+                    file.spawns.len(),
+                    path.display()
+                )),
+                Err(e) => log
+                    .category(LogCategory::Replay)
+                    .warn(format!("Could not save {}: {e}", path.display())),
+            }
+        }
+        (ReplayCommand::ToggleRecording, _) => {
+            let seed = rand::random();
+            let scene = scene_path(&replay.path);
+            rng.reseed(seed);
+            control.deterministic = true;
+            clock.steps = 0;
+            scene_commands.send(SceneCommand::Save(scene.clone()));
+            replay.mode = ReplayMode::Recording(ReplayFile {
+                version: REPLAY_VERSION,
+                seed,
+                scene,
+                spawns: Vec::new(),
+            });
+            log.category(LogCategory::Replay).info("Recording replay");
+        }
+        (ReplayCommand::Play, _) => match read_replay(&path) {
+            Ok(file) => {
+                // Loading the scene resets the step counter.
+                rng.reseed(file.seed);
+                control.deterministic = true;
+                scene_commands.send(SceneCommand::Load(file.scene));
+                replay.mode = ReplayMode::Playing(file.spawns.into());
+                log.category(LogCategory::Replay)
+                    .info(format!("Playing replay {}", path.display()));
+            }
+            Err(e) => log
+                .category(LogCategory::Replay)
+                .warn(format!("Could not load {}: {e}", path.display())),
+        },
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn replay_controls(
+    actions: ActionInput,
+    mut egui_context: ResMut<EguiContext>,
+    bindings: Res<Keybindings>,
+    mut replay: ResMut<Replay>,
+    mut rng: ResMut<SimulationRng>,
+    mut control: ResMut<SimulationControl>,
+    mut clock: ResMut<SimClock>,
+    mut scene_commands: EventWriter<SceneCommand>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let mut command = None;
+    if actions.just_pressed(Action::ToggleRecording) {
+        command = Some(ReplayCommand::ToggleRecording);
+    }
+    if actions.just_pressed(Action::PlayReplay) {
+        command = Some(ReplayCommand::Play);
+    }
+    egui::Window::new("Replay")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.text_edit_singleline(&mut replay.path);
+            ui.horizontal(|ui| {
+                let recording = matches!(replay.mode, ReplayMode::Recording(_));
+                let label = if recording { "Stop" } else { "Record" };
+                if ui
+                    .button(label)
+                    .on_hover_text(bindings.label(Action::ToggleRecording))
+                    .clicked()
+                {
+                    command = Some(ReplayCommand::ToggleRecording);
+                }
+                if ui
+                    .button("Play")
+                    .on_hover_text(bindings.label(Action::PlayReplay))
+                    .clicked()
+                {
+                    command = Some(ReplayCommand::Play);
+                }
+            });
+            match &replay.mode {
+                ReplayMode::Idle => {}
+                ReplayMode::Recording(file) => {
+                    ui.label(format!("Recording: {} spawns", file.spawns.len()));
+                }
+                ReplayMode::Playing(spawns) => {
+                    ui.label(format!("Playing: {} spawns left", spawns.len()));
+                }
+            }
+        });
+    if let Some(command) = command {
+        run_replay_command(
+            command,
+            &mut replay,
+            &mut rng,
+            &mut control,
+            &mut clock,
+            &mut scene_commands,
+            &mut log,
+        );
+    }
+}
+
+fn record_spawns(
+    clock: Res<SimClock>,
+    mut replay: ResMut<Replay>,
+    mut spawn_requests: EventReader<SpawnRequest>,
+    mut placed: EventReader<ParticlePlaced>,
+) {
+    let ReplayMode::Recording(file) = &mut replay.mode else {
+        spawn_requests.clear();
+        placed.clear();
+        return;
+    };
+    for request in spawn_requests.iter() {
+        file.spawns.push(RecordedSpawn {
+            step: clock.steps,
+            position: request.position,
+            count: request.count,
+            placed: None,
+        });
+    }
+    for ParticlePlaced(builder) in placed.iter() {
+        file.spawns
+            .push(RecordedSpawn::placed(clock.steps, builder));
+    }
+}
+
+fn play_spawns(
+    clock: Res<SimClock>,
+    mut replay: ResMut<Replay>,
+    mut spawn_requests: EventWriter<SpawnRequest>,
+    mut spawner: ParticleSpawner,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let ReplayMode::Playing(spawns) = &mut replay.mode else {
+        return;
+    };
+    while spawns
+        .front()
+        .is_some_and(|spawn| spawn.step <= clock.steps)
+    {
+        let spawn = spawns.pop_front().unwrap();
+        match spawn.placed {
+            Some(placed) => {
+                let builder = ParticleBuilder::at(spawn.position)
+                    .rotation(placed.rotation)
+                    .size(placed.size)
+                    .velocity(Velocity {
+                        linvel: placed.linvel,
+                        angvel: placed.angvel,
+                    })
+                    .restitution(placed.restitution)
+                    .pinned(placed.pinned);
+                spawner.place([builder]);
+            }
+            None => spawn_requests.send(SpawnRequest {
+                position: spawn.position,
+                count: spawn.count,
+            }),
+        }
+    }
+    if spawns.is_empty() {
+        replay.mode = ReplayMode::Idle;
+        log.category(LogCategory::Replay).info("Replay finished");
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Replay>()
+            .add_system(replay_controls.label(Pipeline::Input).before(SceneIo))
+            .add_system(
+                record_spawns
+                    .label(Pipeline::Spawning)
+                    .after(Pipeline::Input)
+                    .after(crate::spawn_particles),
+            )
+            .add_system(
+                play_spawns
+                    .label(Pipeline::Spawning)
+                    .after(Pipeline::Input)
+                    .after(SceneIo)
+                    .before(crate::spawn_particles),
+            );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::input::{Action, ActionInput};
+use crate::sim_control::{simulated_delta, SimulationControl};
+use crate::split_screen::ComparisonEntity;
+use crate::{Particle, ParticleCount, ParticleState, Pipeline};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+use std::collections::VecDeque;
+
+struct Snapshot {
+    /// Simulated seconds elapsed when the snapshot was taken.
+    time: f32,
+    particles: Vec<ParticleState>,
+}
+
+/// Ring buffer of particle states recorded every simulated frame, used to
+/// scrub backwards while paused.
+#[derive(Resource)]
+pub struct RewindBuffer {
+    snapshots: VecDeque<Snapshot>,
+    /// How many simulated seconds of history are kept.
+    pub seconds: f32,
+    /// Simulated seconds recorded so far, the clock snapshots are stamped
+    /// with.
+    elapsed: f32,
+    /// Index of the snapshot currently shown while scrubbing.
+    cursor: Option<usize>,
+}
+
+impl Default for RewindBuffer {
+    fn default() -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            seconds: 5.0,
+            elapsed: 0.0,
+            cursor: None,
+        }
+    }
+}
+
+impl RewindBuffer {
+    /// Drops the snapshots older than [`Self::seconds`].
+    fn trim(&mut self) {
+        while self
+            .snapshots
+            .front()
+            .is_some_and(|oldest| self.elapsed - oldest.time > self.seconds)
+        {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Simulated seconds between the snapshot at `index` and the latest one.
+    fn seconds_before_latest(&self, index: usize) -> f32 {
+        self.snapshots
+            .back()
+            .map_or(0.0, |latest| latest.time - self.snapshots[index].time)
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.cursor = None;
+    }
+
+    /// Replaces references to a respawned particle in all snapshots.
+    fn remap(&mut self, old: Entity, new: Entity) {
+        for snapshot in self.snapshots.iter_mut() {
+            for state in snapshot.particles.iter_mut() {
+                if state.entity == old {
+                    state.entity = new;
+                }
+            }
+        }
+    }
+}
+
+fn record_snapshot(
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut buffer: ResMut<RewindBuffer>,
+    particles: Query<
+        (Entity, &Transform, &Velocity, &Particle, &RigidBody),
+        Without<ComparisonEntity>,
+    >,
+) {
+    if !rapier_config.physics_pipeline_active {
+        return;
+    }
+    // Resuming from a scrubbed state discards the abandoned future.
+    if let Some(cursor) = buffer.cursor.take() {
+        buffer.snapshots.truncate(cursor + 1);
+        if let Some(resumed) = buffer.snapshots.back() {
+            buffer.elapsed = resumed.time;
+        }
+    }
+    buffer.elapsed += simulated_delta(&time, &rapier_config);
+    let particles = particles
+        .iter()
+        .map(
+            |(entity, transform, velocity, particle, rigid_body)| ParticleState {
+                entity,
+                transform: *transform,
+                velocity: *velocity,
+                size: particle.size,
+                pinned: *rigid_body == RigidBody::Fixed,
+            },
+        )
+        .collect();
+    let time = buffer.elapsed;
+    buffer.snapshots.push_back(Snapshot { time, particles });
+    buffer.trim();
+}
+
+#[allow(clippy::type_complexity)]
+fn restore_snapshot(
+    mut commands: Commands,
+    mut buffer: ResMut<RewindBuffer>,
+    mut particle_counter: ResMut<ParticleCount>,
+    mut particles: Query<
+        (Entity, &mut Transform, &mut Velocity, &mut RigidBody),
+        (With<Particle>, Without<ComparisonEntity>),
+    >,
+) {
+    if !buffer.is_changed() {
+        return;
+    }
+    let Some(cursor) = buffer.cursor else {
+        return;
+    };
+    let states = buffer.snapshots[cursor].particles.clone();
+
+    for (entity, ..) in particles.iter() {
+        if !states.iter().any(|state| state.entity == entity) {
+            commands.entity(entity).despawn_recursive();
+            particle_counter.0 = particle_counter.0.saturating_sub(1);
+        }
+    }
+    for state in states {
+        if let Ok((_, mut transform, mut velocity, mut rigid_body)) =
+            particles.get_mut(state.entity)
+        {
+            *transform = state.transform;
+            *velocity = state.velocity;
+            let restored = if state.pinned {
+                RigidBody::Fixed
+            } else {
+                RigidBody::Dynamic
+            };
+            if *rigid_body != restored {
+                *rigid_body = restored;
+            }
+        } else {
+            let entity = commands.spawn(state.bundle()).id();
+            particle_counter.0 += 1;
+            buffer.remap(state.entity, entity);
+        }
+    }
+}
+
+fn scrub(control: &SimulationControl, buffer: &mut RewindBuffer, delta: isize) {
+    if !control.paused || buffer.snapshots.is_empty() {
+        return;
+    }
+    let last = buffer.snapshots.len() - 1;
+    let current = buffer.cursor.unwrap_or(last);
+    buffer.cursor = Some(current.saturating_add_signed(delta).min(last));
+}
+
+fn rewind_hotkeys(
+    actions: ActionInput,
+    control: Res<SimulationControl>,
+    mut buffer: ResMut<RewindBuffer>,
+) {
+    if actions.just_pressed(Action::RewindBack) {
+        scrub(&control, &mut buffer, -1);
+    }
+    if actions.just_pressed(Action::RewindForward) {
+        scrub(&control, &mut buffer, 1);
+    }
+}
+
+fn rewind_ui(
+    mut egui_context: ResMut<EguiContext>,
+    control: Res<SimulationControl>,
+    mut buffer: ResMut<RewindBuffer>,
+) {
+    egui::Window::new("Rewind")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let len = buffer.snapshots.len();
+            if len == 0 {
+                ui.label("No history recorded yet.");
+                return;
+            }
+            let last = len - 1;
+            let mut index = buffer.cursor.unwrap_or(last);
+            let seconds_back = buffer.seconds_before_latest(index);
+            let slider = ui.add_enabled(
+                control.paused,
+                egui::Slider::new(&mut index, 0..=last).show_value(false),
+            );
+            ui.label(format!("-{seconds_back:.2} s"));
+            if !control.paused {
+                ui.label("Pause the simulation to scrub (Left/Right).");
+            }
+            if slider.changed() {
+                buffer.cursor = Some(index);
+            }
+        });
+}
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RewindBuffer>()
+            .add_system(rewind_hotkeys.label(Pipeline::Input))
+            .add_system(rewind_ui.label(Pipeline::Ui).after(Pipeline::Visual))
+            .add_system(restore_snapshot.after(rewind_hotkeys).after(rewind_ui))
+            .add_system_to_stage(CoreStage::PostUpdate, record_snapshot);
+    }
+}
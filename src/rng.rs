@@ -0,0 +1,53 @@
+use crate::console::ConsoleLog;
+use crate::sim_control::SimulationControl;
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Source of all randomness used when spawning, so a run can be reproduced
+/// from its seed.
+#[derive(Resource)]
+pub struct SimulationRng {
+    pub seed: u64,
+    pub rng: StdRng,
+}
+
+impl SimulationRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::from_seed(seed);
+    }
+}
+
+fn announce_seed(
+    rng: Res<SimulationRng>,
+    control: Res<SimulationControl>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let mode = if control.deterministic {
+        " (deterministic)"
+    } else {
+        ""
+    };
+    log.info(format!("Random seed {}{mode}", rng.seed));
+}
+
+#[derive(Default)]
+pub struct SimulationRngPlugin {
+    /// Seed to start from; a random one is picked if not given.
+    pub seed: Option<u64>,
+}
+
+impl Plugin for SimulationRngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        app.insert_resource(SimulationRng::from_seed(seed))
+            .add_startup_system(announce_seed);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::camera::{cursor_world_position, MainCamera};
+use crate::input::{Action, ActionInput};
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::tools::{pointer_over_ui, Tool};
+use crate::units::{format_length, from_meters, to_pixels};
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+
+/// Candidate grid spacings in meters, the smallest one that is still readable is used.
+const GRID_SPACINGS: [f32; 10] = [0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0];
+/// Minimum on-screen distance between grid lines, in logical pixels.
+const MIN_GRID_PIXELS: f32 = 40.0;
+
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct GridSettings {
+    pub enabled: bool,
+    pub labels: bool,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            labels: true,
+        }
+    }
+}
+
+/// The last distance measured with the measure tool, in world units.
+#[derive(Resource, Default)]
+pub struct Measurement {
+    start: Option<Vec2>,
+    end: Option<Vec2>,
+}
+
+fn toggle_grid(actions: ActionInput, mut settings: ResMut<GridSettings>) {
+    if actions.just_pressed(Action::ToggleGrid) {
+        settings.enabled = !settings.enabled;
+    }
+}
+
+fn draw_grid(
+    settings: Res<GridSettings>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform, &Transform), With<MainCamera>>,
+) {
+    if !settings.enabled {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform, transform) = camera_q.single();
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+    let scale = transform.scale.x;
+    let center = transform.translation.truncate();
+    let min = center - viewport / 2.0 * scale;
+    let max = center + viewport / 2.0 * scale;
+
+    let spacing = GRID_SPACINGS
+        .iter()
+        .map(|&meters| from_meters(meters))
+        .find(|&spacing| to_pixels(spacing, scale) >= MIN_GRID_PIXELS)
+        .unwrap_or(from_meters(1.0));
+
+    let painter = overlay_painter(egui_context.ctx_mut(), "grid", camera);
+    let stroke = egui::Stroke::new(1.0, egui::Color32::from_white_alpha(20));
+    let label_color = egui::Color32::from_white_alpha(90);
+    let font = egui::FontId::monospace(10.0);
+    let to_screen = |point: Vec2| world_to_screen(camera, camera_transform, window, point);
+
+    let mut x = (min.x / spacing).floor() * spacing;
+    while x <= max.x {
+        if let (Some(a), Some(b)) = (
+            to_screen(Vec2::new(x, min.y)),
+            to_screen(Vec2::new(x, max.y)),
+        ) {
+            painter.line_segment([a, b], stroke);
+            if settings.labels {
+                painter.text(
+                    a + egui::vec2(2.0, -2.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    format_length(x),
+                    font.clone(),
+                    label_color,
+                );
+            }
+        }
+        x += spacing;
+    }
+    let mut y = (min.y / spacing).floor() * spacing;
+    while y <= max.y {
+        if let (Some(a), Some(b)) = (
+            to_screen(Vec2::new(min.x, y)),
+            to_screen(Vec2::new(max.x, y)),
+        ) {
+            painter.line_segment([a, b], stroke);
+            if settings.labels {
+                painter.text(
+                    a + egui::vec2(2.0, -2.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    format_length(y),
+                    font.clone(),
+                    label_color,
+                );
+            }
+        }
+        y += spacing;
+    }
+}
+
+fn measure(
+    tool: Res<Tool>,
+    actions: ActionInput,
+    windows: Res<Windows>,
+    mut egui_context: ResMut<EguiContext>,
+    mut measurement: ResMut<Measurement>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    if *tool != Tool::Measure {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let Some(cursor) = cursor_world_position(window, camera, camera_transform) else {
+        return;
+    };
+    if actions.just_pressed(Action::UseTool) && !pointer_over_ui(&mut egui_context) {
+        measurement.start = Some(cursor);
+        measurement.end = Some(cursor);
+    } else if actions.pressed(Action::UseTool) && measurement.start.is_some() {
+        measurement.end = Some(cursor);
+    }
+}
+
+fn draw_measurement(
+    tool: Res<Tool>,
+    measurement: Res<Measurement>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    if *tool != Tool::Measure {
+        return;
+    }
+    let (Some(start), Some(end)) = (measurement.start, measurement.end) else {
+        return;
+    };
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let (Some(a), Some(b)) = (
+        world_to_screen(camera, camera_transform, window, start),
+        world_to_screen(camera, camera_transform, window, end),
+    ) else {
+        return;
+    };
+
+    let painter = overlay_painter(egui_context.ctx_mut(), "measurement", camera);
+    painter.line_segment([a, b], egui::Stroke::new(1.5, egui::Color32::LIGHT_BLUE));
+    painter.circle_filled(a, 2.5, egui::Color32::LIGHT_BLUE);
+    painter.circle_filled(b, 2.5, egui::Color32::LIGHT_BLUE);
+    painter.text(
+        b + egui::vec2(6.0, -6.0),
+        egui::Align2::LEFT_BOTTOM,
+        format_length(start.distance(end)),
+        egui::FontId::monospace(12.0),
+        egui::Color32::WHITE,
+    );
+}
+
+pub struct RulerPlugin;
+
+impl Plugin for RulerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GridSettings>()
+            .init_resource::<Measurement>()
+            .register_type::<GridSettings>()
+            .add_system(toggle_grid.label(Pipeline::Input))
+            .add_system(
+                draw_grid
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning)
+                    .after(toggle_grid),
+            )
+            .add_system(measure.label(Pipeline::Input))
+            .add_system(
+                draw_measurement
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning)
+                    .after(measure),
+            );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::cli::Cli;
+use crate::console::{ConsoleLog, LogCategory};
+use crate::scene::SceneCommand;
+use crate::Pipeline;
+use bevy::asset::FileAssetIo;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use std::path::{Path, PathBuf};
+
+/// Directory of the scenarios shipped with the game, in the asset folder
+/// Bevy loads from, so it is found from any working directory.
+fn builtin_dir() -> PathBuf {
+    FileAssetIo::get_base_path()
+        .join("assets")
+        .join("scenarios")
+}
+
+/// A named scene file that can be loaded from the scenario menu.
+pub struct Scenario {
+    pub name: String,
+    pub path: PathBuf,
+    pub builtin: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct ScenarioLibrary {
+    pub scenarios: Vec<Scenario>,
+}
+
+/// Directory where users can drop their own scenario files.
+fn user_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("physicsboi").join("scenarios"))
+}
+
+fn scan(dir: &Path, builtin: bool) -> Vec<Scenario> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut scenarios: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.replace('_', " ");
+            Some(Scenario {
+                name,
+                path,
+                builtin,
+            })
+        })
+        .collect();
+    scenarios.sort_by(|a, b| a.name.cmp(&b.name));
+    scenarios
+}
+
+impl ScenarioLibrary {
+    pub fn refresh(&mut self) {
+        self.scenarios = scan(&builtin_dir(), true);
+        if let Some(dir) = user_dir() {
+            self.scenarios.extend(scan(&dir, false));
+        }
+    }
+}
+
+/// Resolves a scenario given on the command line, either as a path to a scene
+/// file or as the name of a scenario in the library.
+pub fn resolve(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(name);
+    if path.is_file() {
+        return Some(path);
+    }
+    let name = name.replace('_', " ");
+    let mut library = ScenarioLibrary::default();
+    library.refresh();
+    library
+        .scenarios
+        .into_iter()
+        .find(|scenario| scenario.name.eq_ignore_ascii_case(&name))
+        .map(|scenario| scenario.path)
+}
+
+fn load_library(mut library: ResMut<ScenarioLibrary>) {
+    library.refresh();
+}
+
+fn load_startup_scenario(
+    cli: Option<Res<Cli>>,
+    mut log: ResMut<ConsoleLog>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    let Some(name) = cli.and_then(|cli| cli.scenario.clone()) else {
+        return;
+    };
+    match resolve(&name) {
+        Some(path) => scene_commands.send(SceneCommand::Load(path)),
+        None => log
+            .category(LogCategory::Scene)
+            .warn(format!("Unknown scenario {name}")),
+    }
+}
+
+fn scenarios_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut library: ResMut<ScenarioLibrary>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    egui::Window::new("Scenarios")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            if library.scenarios.is_empty() {
+                ui.label("No scenarios found.");
+            }
+            for scenario in &library.scenarios {
+                let label = if scenario.builtin {
+                    scenario.name.clone()
+                } else {
+                    format!("{} (user)", scenario.name)
+                };
+                if ui.button(label).clicked() {
+                    scene_commands.send(SceneCommand::Load(scenario.path.clone()));
+                }
+            }
+            ui.separator();
+            if ui.button("Refresh").clicked() {
+                library.refresh();
+            }
+            if let Some(dir) = user_dir() {
+                ui.small(format!("Add your own scenes to {}", dir.display()));
+            }
+        });
+}
+
+pub struct ScenariosPlugin;
+
+impl Plugin for ScenariosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScenarioLibrary>()
+            .add_startup_system(load_library)
+            .add_startup_system(load_startup_scenario)
+            .add_system(scenarios_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
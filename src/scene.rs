@@ -0,0 +1,484 @@
+use crate::annotations::{Annotation, Annotations};
+use crate::console::{ConsoleLog, LogCategory};
+use crate::headless::ui_available;
+use crate::history::EditHistory;
+use crate::input::{Action, ActionInput, Keybindings};
+use crate::rewind::RewindBuffer;
+use crate::rng::SimulationRng;
+use crate::sim_control::{SimClock, SimulationControl};
+use crate::split_screen::ComparisonEntity;
+use crate::stats::SimStats;
+use crate::trails::TrailSettings;
+use crate::units::{from_meters, from_millimeters, to_meters};
+use crate::{
+    spawn_default_scene, spawn_wall, Particle, ParticleBuilder, ParticleCount, Particles, Pipeline,
+    Wall,
+};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Bumped whenever the file layout changes incompatibly. Version 1 stored
+/// lengths in world units of a millimeter, version 2 stores them in meters.
+const SCENE_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+struct SceneFile {
+    version: u32,
+    settings: SceneSettings,
+    walls: Vec<WallData>,
+    particles: Vec<ParticleData>,
+    #[serde(default)]
+    annotations: Vec<Annotation>,
+}
+
+impl SceneFile {
+    /// The scene with every length and velocity multiplied by `factor`, to
+    /// convert between the meters of the file and world units.
+    fn scaled(mut self, factor: f32) -> Self {
+        self.settings.gravity *= factor;
+        for wall in &mut self.walls {
+            wall.position *= factor;
+            wall.half_extents *= factor;
+        }
+        for particle in &mut self.particles {
+            particle.position *= factor;
+            particle.linvel *= factor;
+            particle.size *= factor;
+        }
+        for note in &mut self.annotations {
+            note.position *= factor;
+            if let Some(arrow) = &mut note.arrow {
+                *arrow *= factor;
+            }
+        }
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SceneSettings {
+    gravity: Vec2,
+    time_scale: f32,
+    particles_per_click: i32,
+    trails_enabled: bool,
+    trail_length: usize,
+    trail_width: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WallData {
+    position: Vec2,
+    half_extents: Vec2,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ParticleData {
+    position: Vec2,
+    /// Rotation around the z axis in radians.
+    rotation: f32,
+    linvel: Vec2,
+    angvel: f32,
+    size: f32,
+    #[serde(default)]
+    pinned: bool,
+}
+
+/// The resources a scene file restores besides walls and particles.
+#[derive(SystemParam)]
+struct SceneResources<'w, 's> {
+    rapier_config: ResMut<'w, RapierConfiguration>,
+    control: ResMut<'w, SimulationControl>,
+    particles_per_click: ResMut<'w, Particles>,
+    trail_settings: ResMut<'w, TrailSettings>,
+    annotations: ResMut<'w, Annotations>,
+    #[system_param(ignore)]
+    _marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's> SceneResources<'w, 's> {
+    fn restore(&mut self, scene: &SceneFile) {
+        let settings = &scene.settings;
+        self.rapier_config.gravity = settings.gravity;
+        self.control.time_scale = settings.time_scale;
+        self.particles_per_click.0 = settings.particles_per_click;
+        self.trail_settings.enabled = settings.trails_enabled;
+        self.trail_settings.length = settings.trail_length;
+        self.trail_settings.width = settings.trail_width;
+        self.annotations.notes = scene.annotations.clone();
+    }
+}
+
+/// File used by the quick save and load hotkeys and the scene window.
+#[derive(Resource)]
+pub struct ScenePath(pub String);
+
+impl Default for ScenePath {
+    fn default() -> Self {
+        Self("scene.ron".to_string())
+    }
+}
+
+/// Label of the systems handling [`SceneCommand`]s.
+#[derive(SystemLabel)]
+pub struct SceneIo;
+
+pub enum SceneCommand {
+    Save(PathBuf),
+    Load(PathBuf),
+    /// Clears the world and reloads the last loaded scene, or sets up the
+    /// default arena if none was loaded.
+    Reset,
+    /// Keeps the scene in [`StashedScenes`] under the given key instead of
+    /// writing a file.
+    Stash(String),
+    /// Loads and removes a stashed scene.
+    Unstash(String),
+}
+
+/// Sent when [`SceneCommand::Load`], [`SceneCommand::Reset`] or
+/// [`SceneCommand::Unstash`] replaced the world. The new particles exist from
+/// the next stage on.
+pub struct SceneLoaded;
+
+/// Scenes kept in memory by [`SceneCommand::Stash`], e.g. the inactive tabs.
+#[derive(Resource, Default)]
+pub struct StashedScenes(HashMap<String, SceneFile>);
+
+impl StashedScenes {
+    pub fn remove(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+}
+
+/// The scene file the world was last loaded from, which a reset goes back
+/// to.
+#[derive(Resource, Default)]
+pub struct LoadedScene(pub Option<PathBuf>);
+
+fn scene_hotkeys(
+    actions: ActionInput,
+    path: Res<ScenePath>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    if actions.just_pressed(Action::QuickSave) {
+        scene_commands.send(SceneCommand::Save(PathBuf::from(&path.0)));
+    }
+    if actions.just_pressed(Action::QuickLoad) {
+        scene_commands.send(SceneCommand::Load(PathBuf::from(&path.0)));
+    }
+    if actions.just_pressed(Action::Reset) {
+        scene_commands.send(SceneCommand::Reset);
+    }
+}
+
+fn scene_ui(
+    mut egui_context: ResMut<EguiContext>,
+    bindings: Res<Keybindings>,
+    mut path: ResMut<ScenePath>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    egui::Window::new("Scene")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.text_edit_singleline(&mut path.0);
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Save")
+                    .on_hover_text(bindings.label(Action::QuickSave))
+                    .clicked()
+                {
+                    scene_commands.send(SceneCommand::Save(PathBuf::from(&path.0)));
+                }
+                if ui
+                    .button("Load")
+                    .on_hover_text(bindings.label(Action::QuickLoad))
+                    .clicked()
+                {
+                    scene_commands.send(SceneCommand::Load(PathBuf::from(&path.0)));
+                }
+                if ui
+                    .button("Reset")
+                    .on_hover_text(bindings.label(Action::Reset))
+                    .clicked()
+                {
+                    scene_commands.send(SceneCommand::Reset);
+                }
+            });
+        });
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn save_scene(
+    mut scene_commands: EventReader<SceneCommand>,
+    mut log: ResMut<ConsoleLog>,
+    rapier_config: Res<RapierConfiguration>,
+    control: Res<SimulationControl>,
+    particles_per_click: Res<Particles>,
+    trail_settings: Res<TrailSettings>,
+    annotations: Res<Annotations>,
+    mut stashed: ResMut<StashedScenes>,
+    walls: Query<(&Transform, &Wall), Without<ComparisonEntity>>,
+    particles: Query<(&Transform, &Velocity, &Particle, &RigidBody), Without<ComparisonEntity>>,
+) {
+    let capture = || SceneFile {
+        version: SCENE_VERSION,
+        settings: SceneSettings {
+            gravity: rapier_config.gravity,
+            time_scale: control.time_scale,
+            particles_per_click: particles_per_click.0,
+            trails_enabled: trail_settings.enabled,
+            trail_length: trail_settings.length,
+            trail_width: trail_settings.width,
+        },
+        walls: walls
+            .iter()
+            .map(|(transform, wall)| WallData {
+                position: transform.translation.truncate(),
+                half_extents: wall.half_extents,
+            })
+            .collect(),
+        particles: particles
+            .iter()
+            .map(|(transform, velocity, particle, rigid_body)| ParticleData {
+                position: transform.translation.truncate(),
+                rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+                linvel: velocity.linvel,
+                angvel: velocity.angvel,
+                size: particle.size,
+                pinned: *rigid_body == RigidBody::Fixed,
+            })
+            .collect(),
+        annotations: annotations.notes.clone(),
+    };
+    for command in scene_commands.iter() {
+        let path = match command {
+            SceneCommand::Save(path) => path,
+            SceneCommand::Stash(key) => {
+                stashed.0.insert(key.clone(), capture());
+                continue;
+            }
+            _ => continue,
+        };
+        let scene = capture().scaled(to_meters(1.0));
+        let result = ron::ser::to_string_pretty(&scene, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|text| std::fs::write(path, text).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => log
+                .category(LogCategory::Scene)
+                .info(format!("Saved scene to {}", path.display())),
+            Err(e) => log
+                .category(LogCategory::Scene)
+                .warn(format!("Could not save {}: {e}", path.display())),
+        }
+    }
+}
+
+fn read_scene(path: &PathBuf) -> Result<SceneFile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let scene: SceneFile = ron::from_str(&text).map_err(|e| e.to_string())?;
+    if scene.version > SCENE_VERSION {
+        return Err(format!(
+            "scene version {} is newer than supported version {SCENE_VERSION}",
+            scene.version
+        ));
+    }
+    let factor = if scene.version < 2 {
+        from_millimeters(1.0)
+    } else {
+        from_meters(1.0)
+    };
+    Ok(scene.scaled(factor))
+}
+
+/// Despawns all particles and walls and forgets everything recorded about
+/// them.
+fn clear_world(
+    commands: &mut Commands,
+    entities: impl Iterator<Item = Entity>,
+    particle_counter: &mut ParticleCount,
+    history: &mut EditHistory,
+    rewind: &mut RewindBuffer,
+    clock: &mut SimClock,
+) {
+    // Comparison twins are despawned as well and get mirrored again from
+    // the newly spawned particles.
+    for entity in entities {
+        commands.entity(entity).despawn_recursive();
+    }
+    particle_counter.0 = 0;
+    history.clear();
+    rewind.clear();
+    clock.steps = 0;
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn load_scene(
+    mut commands: Commands,
+    mut scene_commands: EventReader<SceneCommand>,
+    mut log: ResMut<ConsoleLog>,
+    mut particle_counter: ResMut<ParticleCount>,
+    mut clock: ResMut<SimClock>,
+    mut resources: SceneResources,
+    mut history: ResMut<EditHistory>,
+    mut rewind: ResMut<RewindBuffer>,
+    mut rng: ResMut<SimulationRng>,
+    mut stats: ResMut<SimStats>,
+    mut loaded: ResMut<LoadedScene>,
+    mut stashed: ResMut<StashedScenes>,
+    mut scene_loaded: EventWriter<SceneLoaded>,
+    walls: Query<Entity, (With<Wall>, Without<ComparisonEntity>)>,
+    particles: Query<Entity, With<Particle>>,
+) {
+    for command in scene_commands.iter() {
+        if let SceneCommand::Unstash(key) = command {
+            let Some(scene) = stashed.0.remove(key) else {
+                log.category(LogCategory::Scene)
+                    .warn(format!("No stashed scene {key}"));
+                continue;
+            };
+            clear_world(
+                &mut commands,
+                walls.iter().chain(particles.iter()),
+                &mut particle_counter,
+                &mut history,
+                &mut rewind,
+                &mut clock,
+            );
+            resources.restore(&scene);
+            spawn_scene(&mut commands, &scene, key, &mut particle_counter, &mut log);
+            scene_loaded.send(SceneLoaded);
+            continue;
+        }
+        let path = match command {
+            SceneCommand::Save(_) | SceneCommand::Stash(_) | SceneCommand::Unstash(_) => continue,
+            SceneCommand::Load(path) => path.clone(),
+            SceneCommand::Reset => {
+                let seed = rng.seed;
+                rng.reseed(seed);
+                *stats = default();
+                if let Some(path) = &loaded.0 {
+                    path.clone()
+                } else {
+                    clear_world(
+                        &mut commands,
+                        walls.iter().chain(particles.iter()),
+                        &mut particle_counter,
+                        &mut history,
+                        &mut rewind,
+                        &mut clock,
+                    );
+                    spawn_default_scene(&mut commands, &mut rng);
+                    particle_counter.0 = 1;
+                    resources.annotations.notes.clear();
+                    log.category(LogCategory::Scene).info("Reset the arena");
+                    scene_loaded.send(SceneLoaded);
+                    continue;
+                }
+            }
+        };
+        let scene = match read_scene(&path) {
+            Ok(scene) => scene,
+            Err(e) => {
+                log.category(LogCategory::Scene)
+                    .warn(format!("Could not load {}: {e}", path.display()));
+                continue;
+            }
+        };
+
+        clear_world(
+            &mut commands,
+            walls.iter().chain(particles.iter()),
+            &mut particle_counter,
+            &mut history,
+            &mut rewind,
+            &mut clock,
+        );
+        loaded.0 = Some(path.clone());
+
+        resources.restore(&scene);
+        let source = path.display().to_string();
+        spawn_scene(
+            &mut commands,
+            &scene,
+            &source,
+            &mut particle_counter,
+            &mut log,
+        );
+        scene_loaded.send(SceneLoaded);
+    }
+}
+
+/// Spawns the walls and particles of `scene`, read from `source`.
+fn spawn_scene(
+    commands: &mut Commands,
+    scene: &SceneFile,
+    source: &str,
+    particle_counter: &mut ParticleCount,
+    log: &mut ConsoleLog,
+) {
+    for wall in &scene.walls {
+        spawn_wall(commands, wall.half_extents, wall.position);
+    }
+    for data in &scene.particles {
+        let particle = ParticleBuilder::at(data.position)
+            .rotation(data.rotation)
+            .size(data.size)
+            .velocity(Velocity {
+                linvel: data.linvel,
+                angvel: data.angvel,
+            })
+            .pinned(data.pinned)
+            .build();
+        match particle {
+            Ok(particle) => {
+                commands.spawn(particle);
+                particle_counter.0 += 1;
+            }
+            Err(e) => log
+                .category(LogCategory::Scene)
+                .warn(format!("Skipped particle in {source}: {e}")),
+        }
+    }
+    log.category(LogCategory::Scene).info(format!(
+        "Loaded {} particles from {source}",
+        particle_counter.0
+    ));
+}
+
+pub struct ScenePlugin;
+
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScenePath>()
+            .init_resource::<LoadedScene>()
+            .init_resource::<StashedScenes>()
+            .add_event::<SceneCommand>()
+            .add_event::<SceneLoaded>()
+            .add_system(scene_hotkeys.label(Pipeline::Input))
+            .add_system(
+                scene_ui
+                    .label(Pipeline::Ui)
+                    .after(Pipeline::Visual)
+                    .with_run_criteria(ui_available),
+            )
+            .add_system(
+                save_scene
+                    .label(SceneIo)
+                    .after(Pipeline::Input)
+                    .before(Pipeline::Spawning),
+            )
+            .add_system(
+                load_scene
+                    .label(SceneIo)
+                    .after(Pipeline::Input)
+                    .after(save_scene)
+                    .before(Pipeline::Spawning),
+            );
+    }
+}
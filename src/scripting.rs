@@ -0,0 +1,257 @@
+use crate::cli::Cli;
+use crate::console::{ConsoleLog, LogCategory};
+use crate::sim_control::simulated_delta;
+use crate::stats::SimStats;
+use crate::units::from_meters;
+use crate::{Particle, ParticleCount, Pipeline, SpawnRequest};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rhai::{Dynamic, Engine, EvalAltResult, FnPtr, Map, AST, FLOAT, INT};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Something a script asked for, carried out by [`run_scripts`] since script
+/// functions have no access to the world.
+enum ScriptRequest {
+    Spawn { position: Vec2, count: i32 },
+    ApplyForce(Vec2),
+    Log(String),
+}
+
+/// Callback registered with `every` or `after`.
+struct ScriptTimer {
+    /// Simulated seconds at which the callback runs next.
+    due: f32,
+    /// Seconds between runs, `None` for one-shot callbacks.
+    interval: Option<f32>,
+    callback: FnPtr,
+}
+
+/// State shared between the engine's registered functions and the systems.
+#[derive(Default)]
+struct ScriptShared {
+    /// Simulated seconds since the script started.
+    time: f32,
+    stats: Map,
+    requests: Vec<ScriptRequest>,
+    timers: Vec<ScriptTimer>,
+}
+
+type Shared = Arc<Mutex<ScriptShared>>;
+
+/// The loaded script, kept around so its callbacks can be called later.
+#[derive(Resource)]
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    shared: Shared,
+}
+
+/// Force applied to every particle by the script's `apply_force`.
+#[derive(Resource, Default)]
+struct ScriptForce(Vec2);
+
+/// Accepts both integer and float literals, which Rhai does not convert
+/// between on its own.
+fn number(value: Dynamic) -> Result<f32, Box<EvalAltResult>> {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|i| i as FLOAT))
+        .map(|n| n as f32)
+        .map_err(|_| format!("expected a number, got {}", value.type_name()).into())
+}
+
+fn engine(shared: &Shared) -> Engine {
+    let mut engine = Engine::new();
+
+    let s = shared.clone();
+    engine.register_fn("spawn", move |x: Dynamic, y: Dynamic, count: INT| {
+        let position = Vec2::new(from_meters(number(x)?), from_meters(number(y)?));
+        s.lock().unwrap().requests.push(ScriptRequest::Spawn {
+            position,
+            count: count.clamp(0, i32::MAX as INT) as i32,
+        });
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let s = shared.clone();
+    engine.register_fn("apply_force", move |x: Dynamic, y: Dynamic| {
+        // Rapier takes forces in world units, so newtons scale like meters.
+        let force = Vec2::new(from_meters(number(x)?), from_meters(number(y)?));
+        s.lock()
+            .unwrap()
+            .requests
+            .push(ScriptRequest::ApplyForce(force));
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let s = shared.clone();
+    engine.register_fn("stats", move || s.lock().unwrap().stats.clone());
+    let s = shared.clone();
+    engine.register_fn("time", move || s.lock().unwrap().time as FLOAT);
+    let s = shared.clone();
+    engine.register_fn("every", move |seconds: Dynamic, callback: FnPtr| {
+        let interval = number(seconds)?.max(f32::EPSILON);
+        let mut shared = s.lock().unwrap();
+        let due = shared.time + interval;
+        shared.timers.push(ScriptTimer {
+            due,
+            interval: Some(interval),
+            callback,
+        });
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let s = shared.clone();
+    engine.register_fn("after", move |seconds: Dynamic, callback: FnPtr| {
+        let delay = number(seconds)?;
+        let mut shared = s.lock().unwrap();
+        let due = shared.time + delay;
+        shared.timers.push(ScriptTimer {
+            due,
+            interval: None,
+            callback,
+        });
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let s = shared.clone();
+    engine.on_print(move |text| {
+        s.lock()
+            .unwrap()
+            .requests
+            .push(ScriptRequest::Log(text.to_string()));
+    });
+    engine
+}
+
+impl Script {
+    /// Compiles and runs the top level of a script file. Callbacks it
+    /// registers run later from [`run_scripts`].
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let shared = Shared::default();
+        let engine = engine(&shared);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        engine.run_ast(&ast).map_err(|e| e.to_string())?;
+        Ok(Self {
+            engine,
+            ast,
+            shared,
+        })
+    }
+}
+
+fn load_startup_script(cli: Option<Res<Cli>>, mut log: ResMut<ConsoleLog>, mut commands: Commands) {
+    let Some(path) = cli.and_then(|cli| cli.script.clone()) else {
+        return;
+    };
+    match Script::load(&path) {
+        Ok(script) => {
+            log.category(LogCategory::Script)
+                .info(format!("Running script {}", path.display()));
+            commands.insert_resource(script);
+        }
+        Err(e) => log
+            .category(LogCategory::Script)
+            .warn(format!("Could not run script {}: {e}", path.display())),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_scripts(
+    script: Option<Res<Script>>,
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    stats: Res<SimStats>,
+    particle_count: Res<ParticleCount>,
+    mut force: ResMut<ScriptForce>,
+    mut log: ResMut<ConsoleLog>,
+    mut spawn_requests: EventWriter<SpawnRequest>,
+) {
+    let Some(script) = script else {
+        return;
+    };
+
+    // Callbacks lock the shared state themselves, so it must not be held
+    // while calling them.
+    let due = {
+        let mut shared = script.shared.lock().unwrap();
+        shared.time += simulated_delta(&time, &rapier_config);
+        let current = &stats.current;
+        let mut map = Map::new();
+        map.insert("particles".into(), (particle_count.0 as INT).into());
+        map.insert(
+            "kinetic_energy".into(),
+            (current.kinetic_energy as FLOAT).into(),
+        );
+        map.insert("mean_speed".into(), (current.mean_speed as FLOAT).into());
+        map.insert("max_speed".into(), (current.max_speed as FLOAT).into());
+        map.insert("step".into(), (current.step as INT).into());
+        shared.stats = map;
+
+        let now = shared.time;
+        let mut due = Vec::new();
+        shared.timers.retain_mut(|timer| {
+            if timer.due > now {
+                return true;
+            }
+            due.push(timer.callback.clone());
+            match timer.interval {
+                Some(interval) => {
+                    timer.due += interval;
+                    true
+                }
+                None => false,
+            }
+        });
+        due
+    };
+    for callback in due {
+        if let Err(e) = callback.call::<()>(&script.engine, &script.ast, ()) {
+            log.category(LogCategory::Script)
+                .warn(format!("Script error: {e}"));
+        }
+    }
+
+    let requests = std::mem::take(&mut script.shared.lock().unwrap().requests);
+    for request in requests {
+        match request {
+            ScriptRequest::Spawn { position, count } => {
+                spawn_requests.send(SpawnRequest { position, count });
+            }
+            ScriptRequest::ApplyForce(new_force) => force.0 = new_force,
+            ScriptRequest::Log(message) => log.category(LogCategory::Script).info(message),
+        }
+    }
+}
+
+/// Keeps the script's force on every particle, including ones spawned after
+/// it was set.
+fn apply_script_force(
+    force: Res<ScriptForce>,
+    mut commands: Commands,
+    mut particles: Query<(Entity, Option<&mut ExternalForce>), With<Particle>>,
+) {
+    for (entity, external) in particles.iter_mut() {
+        match external {
+            Some(mut external) if external.force != force.0 => external.force = force.0,
+            None if force.0 != Vec2::ZERO => {
+                commands.entity(entity).insert(ExternalForce {
+                    force: force.0,
+                    torque: 0.0,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Runs a Rhai script given with `--script`, which can spawn particles,
+/// push them around and schedule callbacks in simulated time.
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScriptForce>()
+            .add_startup_system(load_startup_script)
+            .add_system(run_scripts.label(Pipeline::Input))
+            .add_system(apply_script_force.after(run_scripts));
+    }
+}
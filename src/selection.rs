@@ -0,0 +1,224 @@
+use crate::camera::{cursor_world_position, MainCamera};
+use crate::console::{ConsoleLog, LogCategory};
+use crate::history::{EditAction, EditHistory};
+use crate::input::{Action, ActionInput};
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::spatial::SpatialHash;
+use crate::split_screen::ComparisonEntity;
+use crate::{Particle, ParticleCount, ParticleState, Pipeline};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+
+#[derive(Component)]
+pub struct Selected;
+
+/// Returns the particle whose sprite contains `point`, if any.
+pub fn particle_at(
+    hash: &SpatialHash,
+    point: Vec2,
+    particles: &Query<(&Transform, &Particle)>,
+) -> Option<Entity> {
+    hash.candidates_at(point).find(|entity| {
+        particles.get(*entity).is_ok_and(|(transform, particle)| {
+            let local = transform.rotation.inverse() * (point.extend(0.0) - transform.translation);
+            local.x.abs() <= particle.size / 2.0 && local.y.abs() <= particle.size / 2.0
+        })
+    })
+}
+
+/// Screen pixels the cursor may move between pressing and releasing select
+/// for it to still count as a click rather than a box.
+const CLICK_SLOP: f32 = 4.0;
+
+/// Corners of the box being dragged with the select action, in world units.
+#[derive(Resource, Default)]
+struct SelectionDrag {
+    start: Option<Vec2>,
+    end: Option<Vec2>,
+}
+
+/// Clicking adds the particle under the cursor to the selection, or removes
+/// it if it is already selected, and clicking beside particles clears the
+/// selection. Dragging adds every particle whose center is inside the box.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn select_particles(
+    mut commands: Commands,
+    actions: ActionInput,
+    windows: Res<Windows>,
+    spatial_hash: Res<SpatialHash>,
+    mut drag: ResMut<SelectionDrag>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    particles: Query<(&Transform, &Particle)>,
+    boxed: Query<(Entity, &Transform), (With<Particle>, Without<ComparisonEntity>)>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    if let Some(cursor) = cursor_world_position(window, camera, camera_transform) {
+        if actions.just_pressed(Action::Select) {
+            drag.start = Some(cursor);
+        }
+        if drag.start.is_some() && actions.pressed(Action::Select) {
+            drag.end = Some(cursor);
+        }
+    }
+    if !actions.just_released(Action::Select) {
+        return;
+    }
+    let (Some(start), Some(end)) = (drag.start.take(), drag.end.take()) else {
+        return;
+    };
+
+    let is_click = match (
+        world_to_screen(camera, camera_transform, window, start),
+        world_to_screen(camera, camera_transform, window, end),
+    ) {
+        (Some(a), Some(b)) => a.distance(b) <= CLICK_SLOP,
+        _ => true,
+    };
+    if !is_click {
+        let (min, max) = (start.min(end), start.max(end));
+        for (entity, transform) in boxed.iter() {
+            let center = transform.translation.truncate();
+            if center.cmpge(min).all() && center.cmple(max).all() {
+                commands.entity(entity).insert(Selected);
+            }
+        }
+        return;
+    }
+
+    match particle_at(&spatial_hash, start, &particles) {
+        Some(entity) if selected.contains(entity) => {
+            commands.entity(entity).remove::<Selected>();
+        }
+        Some(entity) => {
+            commands.entity(entity).insert(Selected);
+        }
+        None => {
+            for entity in selected.iter() {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+    }
+}
+
+fn delete_selected(
+    mut commands: Commands,
+    actions: ActionInput,
+    mut history: ResMut<EditHistory>,
+    mut particle_counter: ResMut<ParticleCount>,
+    mut log: ResMut<ConsoleLog>,
+    selected: Query<(Entity, &Transform, &Velocity, &Particle, &RigidBody), With<Selected>>,
+) {
+    if !actions.just_pressed(Action::DeleteSelected) || selected.is_empty() {
+        return;
+    }
+    let states: Vec<_> = selected
+        .iter()
+        .map(
+            |(entity, transform, velocity, particle, rigid_body)| ParticleState {
+                entity,
+                transform: *transform,
+                velocity: *velocity,
+                size: particle.size,
+                pinned: *rigid_body == RigidBody::Fixed,
+            },
+        )
+        .collect();
+    for state in &states {
+        commands.entity(state.entity).despawn_recursive();
+        particle_counter.0 = particle_counter.0.saturating_sub(1);
+    }
+    log.category(LogCategory::Edit)
+        .info(format!("Deleted {} particles", states.len()));
+    history.record(EditAction::Delete(states));
+}
+
+/// Pins the selected particles, or releases them if all of them already are.
+fn toggle_pin_selected(
+    actions: ActionInput,
+    mut log: ResMut<ConsoleLog>,
+    mut selected: Query<(&mut RigidBody, &mut Velocity), With<Selected>>,
+) {
+    if !actions.just_pressed(Action::TogglePin) || selected.is_empty() {
+        return;
+    }
+    let pin = selected
+        .iter()
+        .any(|(rigid_body, _)| *rigid_body != RigidBody::Fixed);
+    for (mut rigid_body, mut velocity) in selected.iter_mut() {
+        if pin {
+            *rigid_body = RigidBody::Fixed;
+            *velocity = Velocity::zero();
+        } else {
+            *rigid_body = RigidBody::Dynamic;
+        }
+    }
+    let verb = if pin { "Pinned" } else { "Released" };
+    log.category(LogCategory::Edit)
+        .info(format!("{verb} {} particles", selected.iter().len()));
+}
+
+fn draw_selection(
+    drag: Res<SelectionDrag>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    selected: Query<(&Transform, &Particle, &RigidBody), With<Selected>>,
+) {
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let painter = overlay_painter(egui_context.ctx_mut(), "selection", camera);
+
+    if let (Some(start), Some(end)) = (drag.start, drag.end) {
+        let corners = (
+            world_to_screen(camera, camera_transform, window, start),
+            world_to_screen(camera, camera_transform, window, end),
+        );
+        if let (Some(a), Some(b)) = corners {
+            painter.rect_stroke(
+                egui::Rect::from_two_pos(a, b),
+                0.0,
+                egui::Stroke::new(1.0, egui::Color32::YELLOW),
+            );
+        }
+    }
+
+    for (transform, particle, rigid_body) in selected.iter() {
+        let center = transform.translation.truncate();
+        let half = Vec2::splat(particle.size);
+        let corners = (
+            world_to_screen(camera, camera_transform, window, center - half),
+            world_to_screen(camera, camera_transform, window, center + half),
+        );
+        if let (Some(a), Some(b)) = corners {
+            let color = if *rigid_body == RigidBody::Fixed {
+                egui::Color32::LIGHT_BLUE
+            } else {
+                egui::Color32::YELLOW
+            };
+            painter.rect_stroke(
+                egui::Rect::from_two_pos(a, b),
+                0.0,
+                egui::Stroke::new(1.0, color),
+            );
+        }
+    }
+}
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionDrag>()
+            .add_system(select_particles.label(Pipeline::Input))
+            .add_system(delete_selected.label(Pipeline::Input))
+            .add_system(toggle_pin_selected.label(Pipeline::Input))
+            .add_system(
+                draw_selection
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            );
+    }
+}
@@ -0,0 +1,213 @@
+use crate::audio::AudioSettings;
+use crate::autosave::AutosaveSettings;
+use crate::camera::CameraSettings;
+use crate::console::{ConsoleLog, ConsoleVisible};
+use crate::effects::EffectSettings;
+use crate::hud::HudVisible;
+use crate::input::Keybindings;
+use crate::quality::QualitySettings;
+use crate::ruler::GridSettings;
+use crate::tooltip::TooltipSettings;
+use crate::trails::TrailSettings;
+use crate::Particles;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Seconds between checks for changed settings, so dragging a slider or
+/// resizing the window does not rewrite the file every frame.
+const SAVE_INTERVAL: f32 = 1.0;
+
+/// Bumped whenever a default changes in a way saved settings need migrating
+/// for, see [`Keybindings::migrate`].
+const SETTINGS_VERSION: u32 = 1;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub width: f32,
+    pub height: f32,
+    pub vsync: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 800.0,
+            height: 600.0,
+            vsync: true,
+        }
+    }
+}
+
+/// Preferences kept between sessions in the platform config directory.
+/// Missing fields fall back to their defaults, so older files keep loading.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    /// Files written before versioning have none and read as 0.
+    #[serde(default)]
+    pub version: u32,
+    pub window: WindowSettings,
+    pub particles_per_click: i32,
+    pub console_open: bool,
+    pub hud_visible: bool,
+    pub camera: CameraSettings,
+    pub trails: TrailSettings,
+    pub grid: GridSettings,
+    pub autosave: AutosaveSettings,
+    pub keybindings: Keybindings,
+    pub quality: QualitySettings,
+    pub audio: AudioSettings,
+    pub tooltips: TooltipSettings,
+    pub effects: EffectSettings,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            window: default(),
+            particles_per_click: 1,
+            console_open: false,
+            hud_visible: true,
+            camera: default(),
+            trails: default(),
+            grid: default(),
+            autosave: default(),
+            keybindings: default(),
+            quality: default(),
+            audio: default(),
+            tooltips: default(),
+            effects: default(),
+        }
+    }
+}
+
+fn settings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("physicsboi").join("settings.ron"))
+}
+
+impl UserSettings {
+    /// Reads the settings file, falling back to defaults if it is missing or
+    /// unreadable.
+    pub fn load() -> Self {
+        settings_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| ron::from_str::<Self>(&text).ok())
+            .map(|mut settings| {
+                settings.keybindings.migrate(settings.version);
+                settings.version = SETTINGS_VERSION;
+                settings.keybindings.fill_missing();
+                settings
+            })
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = settings_path().ok_or("no config directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
+
+/// The live resources persisted in [`UserSettings`].
+#[derive(SystemParam)]
+struct LiveSettings<'w, 's> {
+    windows: Res<'w, Windows>,
+    particles_per_click: Res<'w, Particles>,
+    console_visible: Res<'w, ConsoleVisible>,
+    hud_visible: Res<'w, HudVisible>,
+    camera: Res<'w, CameraSettings>,
+    trails: Res<'w, TrailSettings>,
+    grid: Res<'w, GridSettings>,
+    autosave: Res<'w, AutosaveSettings>,
+    keybindings: Res<'w, Keybindings>,
+    quality: Res<'w, QualitySettings>,
+    audio: Res<'w, AudioSettings>,
+    tooltips: Res<'w, TooltipSettings>,
+    effects: Res<'w, EffectSettings>,
+    #[system_param(ignore)]
+    _marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's> LiveSettings<'w, 's> {
+    /// Copies the current values over `settings`.
+    fn capture(&self, settings: &mut UserSettings) {
+        if let Some(window) = self.windows.get_primary() {
+            settings.window.width = window.width();
+            settings.window.height = window.height();
+        }
+        settings.particles_per_click = self.particles_per_click.0;
+        settings.console_open = self.console_visible.0;
+        settings.hud_visible = self.hud_visible.0;
+        settings.camera = self.camera.clone();
+        settings.trails = self.trails.clone();
+        settings.grid = self.grid.clone();
+        settings.autosave = self.autosave.clone();
+        settings.keybindings = self.keybindings.clone();
+        settings.quality = self.quality.clone();
+        settings.audio = self.audio.clone();
+        settings.tooltips = self.tooltips.clone();
+        settings.effects = self.effects.clone();
+    }
+}
+
+fn save_changed_settings(
+    time: Res<Time>,
+    mut last_check: Local<f32>,
+    mut saved: ResMut<UserSettings>,
+    mut log: ResMut<ConsoleLog>,
+    live: LiveSettings,
+) {
+    let now = time.elapsed_seconds();
+    if now - *last_check < SAVE_INTERVAL {
+        return;
+    }
+    *last_check = now;
+
+    let mut current = saved.clone();
+    live.capture(&mut current);
+    if current == *saved {
+        return;
+    }
+    if let Err(e) = current.save() {
+        log.warn(format!("Could not save settings: {e}"));
+    }
+    *saved = current;
+}
+
+/// Inserts the loaded settings as the initial resources. Has to be added
+/// before the plugins owning those resources.
+pub struct SettingsPlugin {
+    pub settings: UserSettings,
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = &self.settings;
+        app.insert_resource(Particles(settings.particles_per_click))
+            .insert_resource(ConsoleVisible(settings.console_open))
+            .insert_resource(HudVisible(settings.hud_visible))
+            .insert_resource(settings.camera.clone())
+            .insert_resource(settings.trails.clone())
+            .insert_resource(settings.grid.clone())
+            .insert_resource(settings.autosave.clone())
+            .insert_resource(settings.keybindings.clone())
+            .insert_resource(settings.quality.clone())
+            .insert_resource(settings.audio.clone())
+            .insert_resource(settings.tooltips.clone())
+            .insert_resource(settings.effects.clone())
+            .insert_resource(settings.clone());
+        // Without a config directory, e.g. in the browser, there is nowhere
+        // to save to.
+        if settings_path().is_some() {
+            app.add_system(save_changed_settings);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::console::ConsoleLog;
+use crate::headless::ui_available;
+use crate::input::{Action, ActionInput, Keybindings};
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+
+/// Length of one physics step at a time scale of 1.
+pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+
+#[derive(Resource)]
+pub struct SimulationControl {
+    pub paused: bool,
+    /// Multiplier applied to simulated time, between 0.1 and 10.
+    pub time_scale: f32,
+    /// Steps physics with a fixed timestep instead of following the frame
+    /// rate, so seeded runs replay identically.
+    pub deterministic: bool,
+    /// Fixed physics steps taken per rendered frame. Above 1 physics always
+    /// uses fixed steps, so slow processes can be fast-forwarded.
+    pub steps_per_frame: u32,
+    step_requested: bool,
+}
+
+impl Default for SimulationControl {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            time_scale: 1.0,
+            deterministic: false,
+            steps_per_frame: 1,
+            step_requested: false,
+        }
+    }
+}
+
+impl SimulationControl {
+    /// Advances a paused simulation by exactly one fixed timestep.
+    pub fn request_step(&mut self) {
+        if self.paused {
+            self.step_requested = true;
+        }
+    }
+}
+
+/// Number of physics steps taken since startup or the last scene load.
+#[derive(Resource, Default)]
+pub struct SimClock {
+    pub steps: u64,
+}
+
+/// Simulated seconds per real second, averaged over the last frames.
+#[derive(Resource, Default)]
+pub struct SimSpeed(pub f32);
+
+/// How strongly each frame counts towards [`SimSpeed`].
+const SPEED_SMOOTHING: f32 = 0.05;
+
+/// Seconds of simulated time that pass this frame.
+pub fn simulated_delta(time: &Time, rapier_config: &RapierConfiguration) -> f32 {
+    if !rapier_config.physics_pipeline_active {
+        return 0.0;
+    }
+    match rapier_config.timestep_mode {
+        TimestepMode::Fixed { dt, .. } => dt,
+        TimestepMode::Variable {
+            max_dt, time_scale, ..
+        } => (time.delta_seconds() * time_scale).min(max_dt),
+        TimestepMode::Interpolated { time_scale, .. } => time.delta_seconds() * time_scale,
+    }
+}
+
+fn advance_clock(
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut clock: ResMut<SimClock>,
+    mut speed: ResMut<SimSpeed>,
+) {
+    if rapier_config.physics_pipeline_active {
+        clock.steps += match rapier_config.timestep_mode {
+            TimestepMode::Fixed { substeps, .. } => substeps as u64,
+            _ => 1,
+        };
+    }
+    let real = time.delta_seconds();
+    if real > 0.0 {
+        let ratio = simulated_delta(&time, &rapier_config) / real;
+        speed.0 += (ratio - speed.0) * SPEED_SMOOTHING;
+    }
+}
+
+fn simulation_hotkeys(
+    actions: ActionInput,
+    mut control: ResMut<SimulationControl>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        control.paused = !control.paused;
+        log.info(if control.paused { "Paused" } else { "Resumed" });
+    }
+    if actions.just_pressed(Action::Step) {
+        control.request_step();
+    }
+}
+
+fn apply_simulation_control(
+    mut control: ResMut<SimulationControl>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    if control.step_requested {
+        control.step_requested = false;
+        rapier_config.physics_pipeline_active = true;
+        rapier_config.timestep_mode = TimestepMode::Fixed {
+            dt: FIXED_TIMESTEP,
+            substeps: 1,
+        };
+    } else if control.steps_per_frame > 1 {
+        // Each substep is a full rapier step of the fixed length.
+        let steps = control.steps_per_frame;
+        rapier_config.physics_pipeline_active = !control.paused;
+        rapier_config.timestep_mode = TimestepMode::Fixed {
+            dt: FIXED_TIMESTEP * control.time_scale * steps as f32,
+            substeps: steps as usize,
+        };
+    } else if control.deterministic {
+        rapier_config.physics_pipeline_active = !control.paused;
+        rapier_config.timestep_mode = TimestepMode::Fixed {
+            dt: FIXED_TIMESTEP * control.time_scale,
+            substeps: 1,
+        };
+    } else {
+        rapier_config.physics_pipeline_active = !control.paused;
+        rapier_config.timestep_mode = TimestepMode::Variable {
+            max_dt: FIXED_TIMESTEP,
+            time_scale: control.time_scale,
+            substeps: 1,
+        };
+    }
+}
+
+fn simulation_ui(
+    mut egui_context: ResMut<EguiContext>,
+    bindings: Res<Keybindings>,
+    speed: Res<SimSpeed>,
+    mut control: ResMut<SimulationControl>,
+) {
+    egui::Window::new("Simulation").show(egui_context.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            let label = if control.paused { "Resume" } else { "Pause" };
+            if ui
+                .button(label)
+                .on_hover_text(bindings.label(Action::Pause))
+                .clicked()
+            {
+                control.paused = !control.paused;
+            }
+            if ui
+                .add_enabled(control.paused, egui::Button::new("Step"))
+                .on_hover_text(bindings.label(Action::Step))
+                .clicked()
+            {
+                control.request_step();
+            }
+        });
+        ui.add(
+            egui::Slider::new(&mut control.time_scale, 0.1..=10.0)
+                .logarithmic(true)
+                .text("Time scale"),
+        );
+        ui.checkbox(&mut control.deterministic, "Fixed timestep")
+            .on_hover_text("Steps physics identically regardless of frame rate");
+        ui.add(
+            egui::Slider::new(&mut control.steps_per_frame, 1..=32)
+                .logarithmic(true)
+                .text("Steps per frame"),
+        )
+        .on_hover_text("Fast-forwards with several fixed steps per rendered frame");
+        ui.label(format!("Simulation speed: {:.2}x real time", speed.0));
+    });
+}
+
+#[derive(Default)]
+pub struct SimulationControlPlugin {
+    /// Start with fixed timesteps, see [`SimulationControl::deterministic`].
+    pub deterministic: bool,
+}
+
+impl Plugin for SimulationControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimulationControl {
+            deterministic: self.deterministic,
+            ..default()
+        })
+        .init_resource::<SimClock>()
+        .init_resource::<SimSpeed>()
+        .add_system(simulation_hotkeys.label(Pipeline::Input))
+        .add_system(
+            simulation_ui
+                .label(Pipeline::Ui)
+                .after(Pipeline::Visual)
+                .with_run_criteria(ui_available),
+        )
+        .add_system(
+            apply_simulation_control
+                .after(simulation_hotkeys)
+                .after(simulation_ui),
+        )
+        .add_system_to_stage(CoreStage::PostUpdate, advance_clock);
+    }
+}
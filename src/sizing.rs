@@ -0,0 +1,72 @@
+use crate::units::WORLD_UNITS_PER_METER;
+use bevy_rapier2d::prelude::*;
+
+/// Smallest edge length of a particle in world units, 1 mm. Smaller sizes are
+/// raised to it so the collider keeps a usable extent.
+pub const MIN_PARTICLE_SIZE: f32 = 0.001 * WORLD_UNITS_PER_METER;
+/// Colliders are shrunk by this part of the edge length on each side, so
+/// resting particles do not leave visible gaps between their sprites.
+const COLLIDER_MARGIN_FRACTION: f32 = 0.025;
+/// Upper bound of the margin on each side, 0.1 mm, reached at the default
+/// size.
+const MAX_COLLIDER_MARGIN: f32 = 0.0001 * WORLD_UNITS_PER_METER;
+
+/// Checks a requested edge length and raises it to [`MIN_PARTICLE_SIZE`] if
+/// it is smaller.
+pub fn validate_size(size: f32) -> Result<f32, String> {
+    if !size.is_finite() || size <= 0.0 {
+        return Err(format!("invalid particle size {size}"));
+    }
+    Ok(size.max(MIN_PARTICLE_SIZE))
+}
+
+/// Half the edge length of the collider of a particle with a validated
+/// `size`. The margin scales with the size, so small particles keep nearly
+/// all of their extent.
+pub fn collider_half_extent(size: f32) -> f32 {
+    let margin = (size * COLLIDER_MARGIN_FRACTION).min(MAX_COLLIDER_MARGIN);
+    size / 2.0 - margin
+}
+
+/// Collider matching the sprite of a particle with a validated `size`.
+pub fn collider(size: f32) -> Collider {
+    let half = collider_half_extent(size);
+    Collider::cuboid(half, half)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::from_millimeters;
+
+    #[test]
+    fn rejects_zero_negative_and_non_finite_sizes() {
+        assert!(validate_size(0.0).is_err());
+        assert!(validate_size(-0.0).is_err());
+        assert!(validate_size(-from_millimeters(4.0)).is_err());
+        assert!(validate_size(f32::NAN).is_err());
+        assert!(validate_size(f32::INFINITY).is_err());
+    }
+
+    #[test]
+    fn raises_tiny_sizes_to_the_minimum() {
+        assert_eq!(
+            validate_size(MIN_PARTICLE_SIZE / 100.0),
+            Ok(MIN_PARTICLE_SIZE)
+        );
+        assert_eq!(validate_size(MIN_PARTICLE_SIZE), Ok(MIN_PARTICLE_SIZE));
+        let large = from_millimeters(500.0);
+        assert_eq!(validate_size(large), Ok(large));
+    }
+
+    #[test]
+    fn collider_margin_scales_and_is_capped() {
+        let margin = |size: f32| size / 2.0 - collider_half_extent(size);
+        let small = MIN_PARTICLE_SIZE;
+        assert!((margin(small) - small * COLLIDER_MARGIN_FRACTION).abs() < 1e-6);
+        assert!(collider_half_extent(small) > 0.0);
+        let large = from_millimeters(500.0);
+        assert!((margin(large) - MAX_COLLIDER_MARGIN).abs() < 1e-4);
+        assert!(collider_half_extent(large) < large / 2.0);
+    }
+}
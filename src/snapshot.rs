@@ -0,0 +1,138 @@
+use crate::console::{ConsoleLog, LogCategory};
+use crate::split_screen::ComparisonEntity;
+use crate::Particle;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// NumPy structured dtype of a snapshot row. Lengths are in world units and
+/// angles in radians, as in the CSV export.
+const DTYPE: &str = "[('id', '<u4'), ('x', '<f4'), ('y', '<f4'), ('rotation', '<f4'), \
+     ('vx', '<f4'), ('vy', '<f4'), ('angvel', '<f4'), ('size', '<f4'), ('pinned', '|u1')]";
+
+/// Request to write every particle to a `.npy` file, loadable with
+/// `numpy.load` and `pandas.DataFrame`.
+pub struct ExportSnapshot(pub PathBuf);
+
+/// Path entered in the Export window.
+#[derive(Resource)]
+pub struct SnapshotPath(pub String);
+
+impl Default for SnapshotPath {
+    fn default() -> Self {
+        Self("snapshot.npy".to_string())
+    }
+}
+
+/// Encodes `rows` rows of [`DTYPE`] as a version 1.0 `.npy` file.
+fn npy(rows: usize, data: &[u8]) -> Vec<u8> {
+    let mut header = format!("{{'descr': {DTYPE}, 'fortran_order': False, 'shape': ({rows},), }}");
+    // Magic, version and length take 10 bytes, and the data has to start at
+    // a multiple of 64.
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut out = Vec::with_capacity(10 + header.len() + data.len());
+    out.extend_from_slice(b"\x93NUMPY\x01\x00");
+    out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    out.extend_from_slice(header.as_bytes());
+    out.extend_from_slice(data);
+    out
+}
+
+#[allow(clippy::type_complexity)]
+fn write_snapshot(
+    path: &Path,
+    particles: &Query<
+        (Entity, &Transform, &Velocity, &Particle, &RigidBody),
+        Without<ComparisonEntity>,
+    >,
+) -> Result<usize, String> {
+    let mut data = Vec::new();
+    let mut rows = 0;
+    for (entity, transform, velocity, particle, rigid_body) in particles.iter() {
+        data.extend_from_slice(&entity.index().to_le_bytes());
+        for value in [
+            transform.translation.x,
+            transform.translation.y,
+            transform.rotation.to_euler(EulerRot::XYZ).2,
+            velocity.linvel.x,
+            velocity.linvel.y,
+            velocity.angvel,
+            particle.size,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push((*rigid_body == RigidBody::Fixed) as u8);
+        rows += 1;
+    }
+    std::fs::write(path, npy(rows, &data)).map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+#[allow(clippy::type_complexity)]
+fn export_snapshot(
+    mut requests: EventReader<ExportSnapshot>,
+    mut log: ResMut<ConsoleLog>,
+    particles: Query<
+        (Entity, &Transform, &Velocity, &Particle, &RigidBody),
+        Without<ComparisonEntity>,
+    >,
+) {
+    for ExportSnapshot(path) in requests.iter() {
+        let log = log.category(LogCategory::Export);
+        match write_snapshot(path, &particles) {
+            Ok(rows) => log.info(format!("Wrote {rows} particles to {}", path.display())),
+            Err(e) => log.warn(format!("Could not write {}: {e}", path.display())),
+        }
+    }
+}
+
+/// Dumps the full particle state as a NumPy array on request.
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapshotPath>()
+            .add_event::<ExportSnapshot>()
+            .add_system(export_snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits an encoded file into its header text and data.
+    fn parse(file: &[u8]) -> (&str, &[u8]) {
+        assert_eq!(&file[..8], b"\x93NUMPY\x01\x00");
+        let length = u16::from_le_bytes([file[8], file[9]]) as usize;
+        let header = std::str::from_utf8(&file[10..10 + length]).unwrap();
+        (header, &file[10 + length..])
+    }
+
+    #[test]
+    fn data_starts_at_a_multiple_of_64() {
+        for rows in [0, 1, 7, 12_345, 1_000_000_000] {
+            let file = npy(rows, &[]);
+            let (header, data) = parse(&file);
+            assert_eq!((10 + header.len()) % 64, 0, "{rows} rows");
+            assert!(header.ends_with('\n'));
+            assert!(data.is_empty());
+        }
+    }
+
+    #[test]
+    fn round_trips_shape_and_data() {
+        let data: Vec<u8> = (0..2 * 33).collect();
+        let file = npy(2, &data);
+        let (header, rest) = parse(&file);
+        let header = header.trim_end();
+        assert!(header.starts_with('{') && header.ends_with('}'));
+        assert!(header.contains(&format!("'descr': {DTYPE}")));
+        assert!(header.contains("'fortran_order': False"));
+        assert!(header.contains("'shape': (2,)"));
+        assert_eq!(rest, data.as_slice());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::headless::ui_available;
+use crate::sim_control::{SimulationControl, FIXED_TIMESTEP};
+use crate::units::{from_meters, WORLD_UNITS_PER_METER};
+use crate::{Particle, Pipeline};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+
+/// When particles get continuous collision detection, which keeps fast
+/// particles from tunneling through walls and each other at some cost.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CcdMode {
+    Off,
+    /// For particles moving far relative to their size in one step.
+    Automatic,
+    Always,
+}
+
+impl CcdMode {
+    const ALL: [(CcdMode, &'static str); 3] = [
+        (CcdMode::Off, "Off"),
+        (CcdMode::Automatic, "Fast particles"),
+        (CcdMode::Always, "Always"),
+    ];
+
+    fn label(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(mode, _)| *mode == self)
+            .map_or("", |(_, label)| label)
+    }
+}
+
+/// Rapier tuning applied to the physics world and every particle.
+#[derive(Resource, Clone, PartialEq)]
+pub struct SolverSettings {
+    pub velocity_iterations: usize,
+    pub friction_iterations: usize,
+    pub stabilization_iterations: usize,
+    /// Error reduction parameter: how much of the penetration is corrected
+    /// per step.
+    pub erp: f32,
+    pub ccd: CcdMode,
+    /// With [`CcdMode::Automatic`], particles moving more than this fraction
+    /// of their size in one step get continuous collision detection.
+    pub ccd_step_fraction: f32,
+    /// Whether resting particles may fall asleep and skip simulation.
+    pub sleep: bool,
+    /// Linear speed in m/s below which a particle may fall asleep.
+    pub sleep_linear_threshold: f32,
+    /// Angular speed in rad/s below which a particle may fall asleep.
+    pub sleep_angular_threshold: f32,
+}
+
+impl Default for SolverSettings {
+    /// Rapier's own solver and sleep defaults, with continuous collision
+    /// detection for fast particles.
+    fn default() -> Self {
+        Self {
+            velocity_iterations: 4,
+            friction_iterations: 8,
+            stabilization_iterations: 1,
+            erp: 0.8,
+            ccd: CcdMode::Automatic,
+            ccd_step_fraction: 0.5,
+            sleep: true,
+            sleep_linear_threshold: 0.4,
+            sleep_angular_threshold: 0.5,
+        }
+    }
+}
+
+impl SolverSettings {
+    /// Whether a particle moving `distance` in one step needs continuous
+    /// collision detection.
+    fn ccd_enabled(&self, particle: &Particle, distance: f32) -> bool {
+        match self.ccd {
+            CcdMode::Off => false,
+            CcdMode::Automatic => distance > particle.size * self.ccd_step_fraction,
+            CcdMode::Always => true,
+        }
+    }
+
+    fn apply_sleeping(&self, sleeping: &mut Sleeping) {
+        let disabled = Sleeping::disabled();
+        if self.sleep {
+            // Rapier divides this by its scale, like every other length.
+            sleeping.linear_threshold = from_meters(self.sleep_linear_threshold);
+            sleeping.angular_threshold = self.sleep_angular_threshold;
+        } else {
+            sleeping.linear_threshold = disabled.linear_threshold;
+            sleeping.angular_threshold = disabled.angular_threshold;
+            sleeping.sleeping = false;
+        }
+    }
+}
+
+/// Applies the settings to the physics world and every particle when they
+/// change, and to particles as they are spawned. Continuous collision
+/// detection follows the speed of each particle.
+fn apply_solver_settings(
+    settings: Res<SolverSettings>,
+    control: Res<SimulationControl>,
+    mut rapier_context: ResMut<RapierContext>,
+    mut particles: Query<(
+        &Particle,
+        &Velocity,
+        &mut Ccd,
+        &mut Sleeping,
+        ChangeTrackers<Particle>,
+    )>,
+) {
+    let changed = settings.is_changed();
+    if changed {
+        let parameters = &mut rapier_context.integration_parameters;
+        parameters.max_velocity_iterations = settings.velocity_iterations;
+        parameters.max_velocity_friction_iterations = settings.friction_iterations;
+        parameters.max_stabilization_iterations = settings.stabilization_iterations;
+        parameters.erp = settings.erp;
+    }
+    let step = FIXED_TIMESTEP * control.time_scale;
+    for (particle, velocity, mut ccd, mut sleeping, trackers) in particles.iter_mut() {
+        if changed || trackers.is_added() {
+            settings.apply_sleeping(&mut sleeping);
+        }
+        let enabled = settings.ccd_enabled(particle, velocity.linvel.length() * step);
+        if ccd.enabled != enabled {
+            ccd.enabled = enabled;
+        }
+    }
+}
+
+fn solver_ui(mut egui_context: ResMut<EguiContext>, mut settings: ResMut<SolverSettings>) {
+    let mut edited = settings.clone();
+    egui::Window::new("Solver")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.add(
+                egui::Slider::new(&mut edited.velocity_iterations, 1..=32)
+                    .text("Velocity iterations"),
+            );
+            ui.add(
+                egui::Slider::new(&mut edited.friction_iterations, 1..=32)
+                    .text("Friction iterations"),
+            );
+            ui.add(
+                egui::Slider::new(&mut edited.stabilization_iterations, 0..=8)
+                    .text("Stabilization iterations"),
+            );
+            ui.add(egui::Slider::new(&mut edited.erp, 0.0..=1.0).text("Error reduction"));
+            egui::ComboBox::from_label("Continuous collision detection")
+                .selected_text(edited.ccd.label())
+                .show_ui(ui, |ui| {
+                    for (mode, label) in CcdMode::ALL {
+                        ui.selectable_value(&mut edited.ccd, mode, label);
+                    }
+                });
+            ui.add_enabled(
+                edited.ccd == CcdMode::Automatic,
+                egui::Slider::new(&mut edited.ccd_step_fraction, 0.05..=2.0)
+                    .text("Size moved per step"),
+            )
+            .on_hover_text(
+                "Particles moving more than this part of their size in one step get CCD",
+            );
+            ui.checkbox(&mut edited.sleep, "Let resting particles sleep");
+            ui.add_enabled_ui(edited.sleep, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut edited.sleep_linear_threshold, 0.0..=2.0)
+                        .text("Sleep below speed (m/s)"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut edited.sleep_angular_threshold, 0.0..=10.0)
+                        .text("Sleep below angular speed (rad/s)"),
+                );
+            });
+            ui.label(format!(
+                "{WORLD_UNITS_PER_METER} world units per meter, fixed when physics starts"
+            ));
+            if ui.button("Reset to defaults").clicked() {
+                edited = default();
+            }
+        });
+    if edited != *settings {
+        *settings = edited;
+    }
+}
+
+/// Runtime-editable Rapier solver settings and their panel.
+pub struct SolverPlugin;
+
+impl Plugin for SolverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SolverSettings>()
+            .add_system(apply_solver_settings.after(Pipeline::Spawning))
+            .add_system(
+                solver_ui
+                    .label(Pipeline::Ui)
+                    .after(Pipeline::Visual)
+                    .with_run_criteria(ui_available),
+            );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::units::from_millimeters;
+use crate::Particle;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Edge length of a hash cell in millimeters, about the size of the largest
+/// spawned particles.
+const CELL_SIZE_MM: f32 = 8.0;
+
+/// Particle positions bucketed into a uniform grid, rebuilt every frame, so
+/// neighbor lookups do not have to scan all particles.
+#[derive(Resource, Default)]
+pub struct SpatialHash {
+    cells: HashMap<IVec2, Vec<(Entity, Vec2)>>,
+    /// Largest distance from the center of an inserted particle to its
+    /// corners, so any particle covering a point has its center this close.
+    reach: f32,
+}
+
+impl SpatialHash {
+    fn cell(position: Vec2) -> IVec2 {
+        (position / from_millimeters(CELL_SIZE_MM))
+            .floor()
+            .as_ivec2()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.reach = 0.0;
+    }
+
+    /// Adds a particle of edge length `size` centered at `position`.
+    pub fn insert(&mut self, entity: Entity, position: Vec2, size: f32) {
+        self.reach = self.reach.max(size * std::f32::consts::FRAC_1_SQRT_2);
+        self.cells
+            .entry(Self::cell(position))
+            .or_default()
+            .push((entity, position));
+    }
+
+    /// Particles whose center lies within `radius` of `position`, including
+    /// a particle at `position` itself.
+    pub fn neighbors_within(
+        &self,
+        position: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        let min = Self::cell(position - Vec2::splat(radius));
+        let max = Self::cell(position + Vec2::splat(radius));
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |(_, other)| other.distance_squared(position) <= radius * radius)
+            .map(|(entity, _)| *entity)
+    }
+
+    /// Particles that may cover `point`, to be checked against their shape.
+    pub fn candidates_at(&self, point: Vec2) -> impl Iterator<Item = Entity> + '_ {
+        self.neighbors_within(point, self.reach)
+    }
+}
+
+/// Rebuilds the hash from the positions written back by the last physics
+/// step.
+fn rebuild_spatial_hash(
+    mut hash: ResMut<SpatialHash>,
+    particles: Query<(Entity, &Transform, &Particle)>,
+) {
+    hash.clear();
+    for (entity, transform, particle) in particles.iter() {
+        hash.insert(entity, transform.translation.truncate(), particle.size);
+    }
+}
+
+pub struct SpatialHashPlugin;
+
+impl Plugin for SpatialHashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialHash>()
+            .add_system_to_stage(CoreStage::PreUpdate, rebuild_spatial_hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(entities: impl Iterator<Item = Entity>) -> Vec<Entity> {
+        let mut entities: Vec<_> = entities.collect();
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn finds_neighbors_across_cell_boundaries() {
+        let cell = from_millimeters(CELL_SIZE_MM);
+        let mut hash = SpatialHash::default();
+        let (left, right) = (Entity::from_raw(0), Entity::from_raw(1));
+        hash.insert(left, Vec2::new(cell - 0.1, 0.0), 1.0);
+        hash.insert(right, Vec2::new(cell + 0.1, 0.0), 1.0);
+        assert_eq!(
+            sorted(hash.neighbors_within(Vec2::new(cell, 0.0), 0.5)),
+            vec![left, right]
+        );
+        assert_eq!(
+            sorted(hash.neighbors_within(Vec2::new(cell - 0.1, 0.0), 0.1)),
+            vec![left]
+        );
+    }
+
+    #[test]
+    fn finds_neighbors_at_negative_coordinates() {
+        let mut hash = SpatialHash::default();
+        let (below, above) = (Entity::from_raw(0), Entity::from_raw(1));
+        hash.insert(below, Vec2::new(-0.5, -0.5), 1.0);
+        hash.insert(above, Vec2::new(0.5, 0.5), 1.0);
+        assert_eq!(
+            sorted(hash.neighbors_within(Vec2::ZERO, 1.0)),
+            vec![below, above]
+        );
+        assert_eq!(
+            sorted(hash.neighbors_within(Vec2::new(-0.5, -0.5), 0.5)),
+            vec![below]
+        );
+    }
+
+    #[test]
+    fn searches_every_cell_a_large_radius_covers() {
+        let cell = from_millimeters(CELL_SIZE_MM);
+        let mut hash = SpatialHash::default();
+        let near = Entity::from_raw(0);
+        let far = Entity::from_raw(1);
+        let outside = Entity::from_raw(2);
+        hash.insert(near, Vec2::ZERO, 1.0);
+        hash.insert(far, Vec2::new(-2.5 * cell, 2.5 * cell), 1.0);
+        hash.insert(outside, Vec2::new(4.0 * cell, 0.0), 1.0);
+        assert_eq!(
+            sorted(hash.neighbors_within(Vec2::ZERO, 3.6 * cell)),
+            vec![near, far]
+        );
+    }
+
+    #[test]
+    fn candidates_reach_the_corners_of_the_largest_particle() {
+        let cell = from_millimeters(CELL_SIZE_MM);
+        let mut hash = SpatialHash::default();
+        let large = Entity::from_raw(0);
+        hash.insert(large, Vec2::ZERO, 4.0 * cell);
+        hash.insert(Entity::from_raw(1), Vec2::splat(10.0 * cell), 1.0);
+        let corner = Vec2::splat(1.99 * cell);
+        assert_eq!(sorted(hash.candidates_at(corner)), vec![large]);
+        hash.clear();
+        assert_eq!(hash.candidates_at(corner).count(), 0);
+    }
+}
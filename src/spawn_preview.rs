@@ -0,0 +1,121 @@
+use crate::camera::{cursor_world_position, MainCamera};
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::placement::Placement;
+use crate::tools::{pointer_over_ui, Tool};
+use crate::units::{from_millimeters, to_pixels};
+use crate::{Particles, Pipeline, PARTICLE_COLOR, SCATTER_DISTANCE, SPAWN_SIZES_MM};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+
+/// Seconds the spawn count stays on screen after it was changed.
+const INDICATOR_SECONDS: f32 = 1.5;
+/// Ghosts drawn per spawn position at most, so a huge count stays readable.
+const MAX_GHOSTS: i32 = 64;
+
+/// When the spawn count was last changed with the wheel or keys, in seconds
+/// since startup.
+#[derive(Resource, Default)]
+pub struct SpawnCountIndicator {
+    changed_at: Option<f32>,
+}
+
+impl SpawnCountIndicator {
+    pub fn show(&mut self, time: &Time) {
+        self.changed_at = Some(time.elapsed_seconds());
+    }
+}
+
+/// Screen color of `color` at `alpha`.
+pub fn translucent(color: Color, alpha: f32) -> egui::Color32 {
+    let [r, g, b, _] = color.as_rgba_f32();
+    egui::Color32::from_rgba_unmultiplied(
+        (r * 255.0) as u8,
+        (g * 255.0) as u8,
+        (b * 255.0) as u8,
+        (alpha * 255.0) as u8,
+    )
+}
+
+/// Draws translucent ghosts of the particles a click would spawn at the
+/// cursor: one per particle around every placement position, at the mean
+/// spawn size, with an outline at the largest. For a moment after the spawn
+/// count changes the ghosts brighten and the count is shown next to them.
+#[allow(clippy::too_many_arguments)]
+fn draw_spawn_preview(
+    indicator: Res<SpawnCountIndicator>,
+    time: Res<Time>,
+    tool: Res<Tool>,
+    particles: Res<Particles>,
+    placement: Res<Placement>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform, &Transform), With<MainCamera>>,
+) {
+    if *tool != Tool::Spawn || pointer_over_ui(&mut egui_context) {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform, transform) = camera_q.single();
+    let Some(cursor) = cursor_world_position(window, camera, camera_transform) else {
+        return;
+    };
+    // 1 while the count was just changed, fading to 0 over the last third.
+    let highlight = indicator.changed_at.map_or(0.0, |changed_at| {
+        let remaining = INDICATOR_SECONDS - (time.elapsed_seconds() - changed_at);
+        (remaining / (INDICATOR_SECONDS / 3.0)).clamp(0.0, 1.0)
+    });
+    let count = particles.0.max(0);
+
+    let painter = overlay_painter(egui_context.ctx_mut(), "spawn_preview", camera);
+    let fill = translucent(PARTICLE_COLOR, 0.15 + 0.2 * highlight);
+    let outline = egui::Stroke::new(1.0, translucent(PARTICLE_COLOR, 0.1 + 0.15 * highlight));
+    let pixels =
+        |millimeters: f32| to_pixels(from_millimeters(millimeters), transform.scale.x).max(2.0);
+    let mean = pixels((SPAWN_SIZES_MM.start + SPAWN_SIZES_MM.end - 1) as f32 / 2.0);
+    let largest = pixels((SPAWN_SIZES_MM.end - 1) as f32);
+    let ghosts = count.min(MAX_GHOSTS);
+    for position in placement.positions(cursor) {
+        for i in 0..ghosts {
+            let angle = i as f32 / ghosts as f32 * std::f32::consts::TAU;
+            let ghost = position + Vec2::new(angle.sin(), angle.cos()) * SCATTER_DISTANCE;
+            if let Some(center) = world_to_screen(camera, camera_transform, window, ghost) {
+                painter.rect_filled(
+                    egui::Rect::from_center_size(center, egui::vec2(mean, mean)),
+                    0.0,
+                    fill,
+                );
+                painter.rect_stroke(
+                    egui::Rect::from_center_size(center, egui::vec2(largest, largest)),
+                    0.0,
+                    outline,
+                );
+            }
+        }
+    }
+    if highlight > 0.0 {
+        if let Some(anchor) = world_to_screen(camera, camera_transform, window, cursor) {
+            let plural = if count == 1 { "" } else { "s" };
+            painter.text(
+                anchor + egui::vec2(16.0, -16.0),
+                egui::Align2::LEFT_BOTTOM,
+                format!("spawn: {count} particle{plural}/click"),
+                egui::FontId::proportional(14.0),
+                egui::Color32::from_white_alpha((220.0 * highlight) as u8),
+            );
+        }
+    }
+}
+
+/// Previews what a click with the spawn tool would create, and makes the
+/// spawn count, changed with the mouse wheel and keys, visible.
+pub struct SpawnPreviewPlugin;
+
+impl Plugin for SpawnPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnCountIndicator>().add_system(
+            draw_spawn_preview
+                .label(Pipeline::Visual)
+                .after(Pipeline::Spawning),
+        );
+    }
+}
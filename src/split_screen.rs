@@ -0,0 +1,208 @@
+use crate::camera::MainCamera;
+use crate::console::ConsoleLog;
+use crate::input::{Action, ActionInput};
+use crate::units::from_meters;
+use crate::{spawn_arena, Particle, ParticleBuilder, Pipeline, BLOOM_INTENSITY};
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::window::WindowResized;
+use bevy_rapier2d::prelude::*;
+
+/// Offset of the comparison arena from the main one, 2 m to the right.
+fn comparison_offset() -> Vec2 {
+    Vec2::new(from_meters(2.0), 0.0)
+}
+
+/// Side-by-side comparison of the main arena with a mirrored copy whose
+/// particles use a different restitution.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct Comparison {
+    pub enabled: bool,
+    /// Restitution of the mirrored particles in the right-hand arena.
+    pub restitution: f32,
+}
+
+impl Default for Comparison {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            restitution: 0.5,
+        }
+    }
+}
+
+/// Marks everything that belongs to the comparison arena.
+#[derive(Component)]
+pub struct ComparisonEntity;
+
+#[derive(Component)]
+struct ComparisonCamera;
+
+fn toggle_comparison(
+    actions: ActionInput,
+    mut comparison: ResMut<Comparison>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    if actions.just_pressed(Action::ToggleComparison) {
+        comparison.enabled = !comparison.enabled;
+        let state = if comparison.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        log.info(format!("Comparison view {state}"));
+    }
+}
+
+fn setup_comparison(
+    mut commands: Commands,
+    comparison: Res<Comparison>,
+    mut active: Local<bool>,
+    entities: Query<Entity, With<ComparisonEntity>>,
+) {
+    if comparison.enabled == *active {
+        return;
+    }
+    *active = comparison.enabled;
+
+    if comparison.enabled {
+        commands.spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    hdr: true,
+                    priority: 1,
+                    ..default()
+                },
+                camera_2d: Camera2d {
+                    // The main camera already cleared the window.
+                    clear_color: ClearColorConfig::None,
+                },
+                ..default()
+            },
+            BloomSettings {
+                intensity: BLOOM_INTENSITY,
+                ..default()
+            },
+            ComparisonCamera,
+            ComparisonEntity,
+        ));
+        for wall in spawn_arena(&mut commands, comparison_offset()) {
+            commands.entity(wall).insert(ComparisonEntity);
+        }
+    } else {
+        for entity in entities.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Spawns a twin in the comparison arena for every particle of the main one.
+/// Twins are left out of [`crate::ParticleCount`], which counts the main
+/// arena.
+fn mirror_particles(
+    mut commands: Commands,
+    comparison: Res<Comparison>,
+    mut was_enabled: Local<bool>,
+    particles: Query<
+        (&Transform, &Velocity, &Particle, ChangeTrackers<Particle>),
+        Without<ComparisonEntity>,
+    >,
+) {
+    let mirror_all = comparison.enabled && !*was_enabled;
+    *was_enabled = comparison.enabled;
+    if !comparison.enabled {
+        return;
+    }
+    for (transform, velocity, particle, tracker) in particles.iter() {
+        if !mirror_all && !tracker.is_added() {
+            continue;
+        }
+        let Ok(twin) = ParticleBuilder::at(transform.translation.truncate() + comparison_offset())
+            .rotation(transform.rotation.to_euler(EulerRot::XYZ).2)
+            .size(particle.size)
+            .velocity(*velocity)
+            .restitution(comparison.restitution)
+            .build()
+        else {
+            continue;
+        };
+        commands.spawn((twin, ComparisonEntity));
+    }
+}
+
+fn layout_viewports(
+    comparison: Res<Comparison>,
+    windows: Res<Windows>,
+    mut resize_events: EventReader<WindowResized>,
+    mut main_camera: Query<&mut Camera, (With<MainCamera>, Without<ComparisonCamera>)>,
+    mut comparison_camera: Query<
+        (&mut Camera, ChangeTrackers<ComparisonCamera>),
+        With<ComparisonCamera>,
+    >,
+) {
+    let resized = resize_events.iter().count() > 0;
+    let spawned = comparison_camera
+        .iter()
+        .any(|(_, tracker)| tracker.is_added());
+    if !resized && !spawned && !comparison.is_changed() {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let half_size = UVec2::new(window.physical_width() / 2, window.physical_height());
+
+    let mut camera = main_camera.single_mut();
+    if !comparison.enabled {
+        camera.viewport = None;
+        return;
+    }
+    camera.viewport = Some(Viewport {
+        physical_position: UVec2::ZERO,
+        physical_size: half_size,
+        ..default()
+    });
+    for (mut camera, _) in comparison_camera.iter_mut() {
+        camera.viewport = Some(Viewport {
+            physical_position: UVec2::new(half_size.x, 0),
+            physical_size: half_size,
+            ..default()
+        });
+    }
+}
+
+/// Keeps the comparison camera framing the same region as the main camera.
+fn sync_comparison_camera(
+    main_camera: Query<&Transform, (With<MainCamera>, Without<ComparisonCamera>)>,
+    mut comparison_camera: Query<&mut Transform, With<ComparisonCamera>>,
+) {
+    let main_transform = main_camera.single();
+    for mut transform in comparison_camera.iter_mut() {
+        *transform = main_transform
+            .with_translation(main_transform.translation + comparison_offset().extend(0.0));
+    }
+}
+
+pub struct SplitScreenPlugin;
+
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Comparison>()
+            .register_type::<Comparison>()
+            .add_system(toggle_comparison.label(Pipeline::Input))
+            .add_system(setup_comparison.after(toggle_comparison))
+            .add_system(mirror_particles.after(setup_comparison))
+            .add_system(
+                layout_viewports
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning)
+                    .after(setup_comparison),
+            )
+            .add_system(
+                sync_comparison_camera
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning),
+            );
+    }
+}
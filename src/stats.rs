@@ -0,0 +1,126 @@
+use crate::headless::ui_available;
+use crate::sim_control::SimClock;
+use crate::units::{format_energy, format_speed, to_meters};
+use crate::{Particle, Pipeline};
+use bevy::prelude::*;
+use bevy::time::FixedTimestep;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+use std::collections::VecDeque;
+
+pub const STATS_TIMESTEP: f64 = 1.0 / 60.0;
+/// Number of samples kept for the charts (10 s at the stats rate).
+const HISTORY_LENGTH: usize = 600;
+
+#[derive(Clone, Copy, Default)]
+pub struct StatsSample {
+    /// Seconds since startup.
+    pub time: f64,
+    /// Physics steps taken, see [`SimClock`].
+    pub step: u64,
+    pub particle_count: usize,
+    /// Total translational kinetic energy in joules.
+    pub kinetic_energy: f32,
+    /// Mean speed in m/s.
+    pub mean_speed: f32,
+    pub max_speed: f32,
+}
+
+/// Aggregate simulation statistics, sampled every fixed step.
+#[derive(Resource, Default, Clone)]
+pub struct SimStats {
+    pub current: StatsSample,
+    pub history: VecDeque<StatsSample>,
+}
+
+pub fn update_stats(
+    time: Res<Time>,
+    clock: Res<SimClock>,
+    mut stats: ResMut<SimStats>,
+    particles: Query<(&Velocity, &ReadMassProperties), With<Particle>>,
+) {
+    let mut sample = StatsSample {
+        time: time.elapsed_seconds_f64(),
+        step: clock.steps,
+        ..default()
+    };
+    let mut total_speed = 0.0;
+    for (velocity, mass_properties) in particles.iter() {
+        let speed = to_meters(velocity.linvel.length());
+        sample.particle_count += 1;
+        sample.kinetic_energy += 0.5 * mass_properties.0.mass * speed * speed;
+        sample.max_speed = sample.max_speed.max(speed);
+        total_speed += speed;
+    }
+    if sample.particle_count > 0 {
+        sample.mean_speed = total_speed / sample.particle_count as f32;
+    }
+
+    stats.current = sample;
+    stats.history.push_back(sample);
+    while stats.history.len() > HISTORY_LENGTH {
+        stats.history.pop_front();
+    }
+}
+
+fn plot(
+    ui: &mut egui::Ui,
+    id: &str,
+    history: &VecDeque<StatsSample>,
+    value: fn(&StatsSample) -> f64,
+) {
+    let points: egui::plot::PlotPoints = history.iter().map(|s| [s.time, value(s)]).collect();
+    egui::plot::Plot::new(id)
+        .height(80.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .show(ui, |plot_ui| plot_ui.line(egui::plot::Line::new(points)));
+}
+
+fn stats_ui(mut egui_context: ResMut<EguiContext>, stats: Res<SimStats>) {
+    egui::Window::new("Statistics")
+        .default_open(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let current = &stats.current;
+            ui.label(format!("Particles: {}", current.particle_count));
+            ui.label(format!(
+                "Kinetic energy: {}",
+                format_energy(current.kinetic_energy)
+            ));
+            ui.label(format!(
+                "Speed: mean {}, max {}",
+                format_speed(current.mean_speed),
+                format_speed(current.max_speed)
+            ));
+            ui.separator();
+            ui.label("Particle count");
+            plot(ui, "particle_count", &stats.history, |s| {
+                s.particle_count as f64
+            });
+            ui.label("Kinetic energy (J)");
+            plot(ui, "kinetic_energy", &stats.history, |s| {
+                s.kinetic_energy as f64
+            });
+            ui.label("Mean speed (m/s)");
+            plot(ui, "mean_speed", &stats.history, |s| s.mean_speed as f64);
+        });
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimStats>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedTimestep::step(STATS_TIMESTEP))
+                    .with_system(update_stats),
+            )
+            .add_system(
+                stats_ui
+                    .label(Pipeline::Ui)
+                    .after(Pipeline::Visual)
+                    .with_run_criteria(ui_available),
+            );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::cli::Cli;
+use crate::experiment::Quantity;
+use crate::headless::headless_app;
+use crate::scenarios::resolve;
+use crate::scene::{SceneCommand, SceneLoaded};
+use crate::sim_control::{SimClock, SimulationControl, FIXED_TIMESTEP};
+use crate::stats::{update_stats, SimStats};
+use crate::units::from_meters;
+use crate::{Particle, ParticleBuilder};
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// A setting varied between the runs of a sweep.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Parameter {
+    /// Vertical gravity in m/s², negative pointing down.
+    Gravity,
+    /// Factor applied to the size of every particle.
+    ParticleSize,
+    /// Restitution coefficient of every particle.
+    Restitution,
+    /// Factor applied to the initial velocity of every particle.
+    Speed,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Axis {
+    pub parameter: Parameter,
+    pub values: Vec<f32>,
+}
+
+/// Outcome recorded for every run.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Metric {
+    /// The quantity at the end of the run.
+    Final(Quantity),
+    /// Simulated seconds after which the quantity stays below the value,
+    /// e.g. the time until the particles come to rest. Left empty if it is
+    /// not below at the end.
+    SettledBelow(Quantity, f32),
+}
+
+impl Metric {
+    /// Name of the metric for the CSV, without commas.
+    fn label(self) -> String {
+        match self {
+            Metric::Final(quantity) => format!("{quantity:?}"),
+            Metric::SettledBelow(quantity, limit) => {
+                format!("{quantity:?} settled below {limit} (s)")
+            }
+        }
+    }
+}
+
+/// A sweep file: a scene run once per combination of the values of one or
+/// two parameters.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Sweep {
+    /// Scenario name from the library or path to a scene file.
+    pub scene: String,
+    /// Simulated seconds every run lasts.
+    pub duration: f32,
+    /// Overrides `--seed`.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Varied along the columns of the result.
+    pub x: Axis,
+    /// Varied along the rows of the result, if given.
+    #[serde(default)]
+    pub y: Option<Axis>,
+    pub metric: Metric,
+}
+
+impl Sweep {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::from_str(&text).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Resource)]
+struct SweepRun {
+    scene: PathBuf,
+    /// Simulated seconds to run for.
+    duration: f32,
+    settings: Vec<(Parameter, f32)>,
+    metric: Metric,
+    /// Whether the parameters were applied to the loaded scene.
+    prepared: bool,
+    /// Simulated seconds since which a [`Metric::SettledBelow`] quantity has
+    /// been below its limit.
+    settled_since: Option<f32>,
+    /// Filled when the metric is known, since the app is gone once the run
+    /// returns.
+    result: Arc<Mutex<Option<f32>>>,
+}
+
+/// Loads the scene with physics held until the parameters are applied.
+fn start_run(
+    run: Res<SweepRun>,
+    mut control: ResMut<SimulationControl>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    control.paused = true;
+    scene_commands.send(SceneCommand::Load(run.scene.clone()));
+}
+
+/// Respawns the particles of the loaded scene with the parameters of the run
+/// and releases physics. Runs after the stage the scene was spawned in, so
+/// its particles exist.
+fn apply_parameters(
+    mut commands: Commands,
+    mut run: ResMut<SweepRun>,
+    mut scene_loaded: EventReader<SceneLoaded>,
+    mut control: ResMut<SimulationControl>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    particles: Query<(Entity, &Transform, &Velocity, &Particle, &RigidBody)>,
+) {
+    let loaded = scene_loaded.iter().count() > 0;
+    if run.prepared || !loaded {
+        return;
+    }
+    run.prepared = true;
+    control.paused = false;
+    for (parameter, value) in &run.settings {
+        if let Parameter::Gravity = parameter {
+            rapier_config.gravity.y = from_meters(*value);
+        }
+    }
+    for (entity, transform, velocity, particle, rigid_body) in particles.iter() {
+        let mut builder = ParticleBuilder::at(transform.translation.truncate())
+            .rotation(transform.rotation.to_euler(EulerRot::XYZ).2)
+            .size(particle.size)
+            .velocity(*velocity)
+            .pinned(*rigid_body == RigidBody::Fixed);
+        for &(parameter, value) in &run.settings {
+            builder = match parameter {
+                Parameter::Gravity => builder,
+                Parameter::ParticleSize => builder.size(particle.size * value),
+                Parameter::Restitution => builder.restitution(value),
+                Parameter::Speed => builder.velocity(Velocity {
+                    linvel: velocity.linvel * value,
+                    angvel: velocity.angvel * value,
+                }),
+            };
+        }
+        commands.entity(entity).despawn_recursive();
+        if let Ok(particle) = builder.build() {
+            commands.spawn(particle);
+        }
+    }
+}
+
+/// Tracks the metric and records it at the end of the run.
+fn measure(
+    mut run: ResMut<SweepRun>,
+    clock: Res<SimClock>,
+    stats: Res<SimStats>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if !run.prepared || clock.steps == 0 {
+        return;
+    }
+    let now = clock.steps as f32 * FIXED_TIMESTEP;
+    if let Metric::SettledBelow(quantity, limit) = run.metric {
+        if quantity.measure(&stats) >= limit {
+            run.settled_since = None;
+        } else if run.settled_since.is_none() {
+            run.settled_since = Some(now);
+        }
+    }
+    if now < run.duration {
+        return;
+    }
+    *run.result.lock().unwrap() = match run.metric {
+        Metric::Final(quantity) => Some(quantity.measure(&stats)),
+        Metric::SettledBelow(..) => run.settled_since,
+    };
+    exit.send(AppExit);
+}
+
+fn run_once(
+    cli: &Cli,
+    sweep: &Sweep,
+    scene: &Path,
+    settings: Vec<(Parameter, f32)>,
+) -> Option<f32> {
+    let result = Arc::new(Mutex::new(None));
+    headless_app(cli)
+        .insert_resource(SweepRun {
+            scene: scene.to_path_buf(),
+            duration: sweep.duration,
+            settings,
+            metric: sweep.metric,
+            prepared: false,
+            settled_since: None,
+            result: result.clone(),
+        })
+        .add_startup_system(start_run)
+        .add_system_to_stage(CoreStage::PostUpdate, apply_parameters)
+        .add_system(measure.after(update_stats))
+        .run();
+    let result = *result.lock().unwrap();
+    result
+}
+
+/// Runs the sweep in `cli.sweep` without a window and writes the metric of
+/// every run to `<output>.sweep.csv`, one column per x value and one row per
+/// y value.
+pub fn run(mut cli: Cli) {
+    let Some(path) = cli.sweep.clone() else {
+        return;
+    };
+    let sweep = match Sweep::load(&path) {
+        Ok(sweep) => sweep,
+        Err(e) => {
+            eprintln!("Could not load {}: {e}", path.display());
+            return;
+        }
+    };
+    let Some(scene) = resolve(&sweep.scene) else {
+        eprintln!("Unknown scenario {}", sweep.scene);
+        return;
+    };
+    cli.seed = sweep.seed.or(cli.seed);
+
+    let x = &sweep.x;
+    let rows: Vec<Option<f32>> = match &sweep.y {
+        Some(y) => y.values.iter().copied().map(Some).collect(),
+        None => vec![None],
+    };
+    let mut csv = match &sweep.y {
+        Some(y) => format!("{:?} \\ {:?}", y.parameter, x.parameter),
+        None => format!("{:?}", x.parameter),
+    };
+    for value in &x.values {
+        let _ = write!(csv, ",{value}");
+    }
+    csv.push('\n');
+
+    for row in rows {
+        match row {
+            Some(value) => {
+                let _ = write!(csv, "{value}");
+            }
+            None => csv.push_str(&sweep.metric.label()),
+        }
+        for &value in &x.values {
+            let mut settings = vec![(x.parameter, value)];
+            if let (Some(row), Some(y)) = (row, &sweep.y) {
+                settings.push((y.parameter, row));
+            }
+            let label = settings
+                .iter()
+                .map(|(parameter, value)| format!("{parameter:?}={value}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let result = run_once(&cli, &sweep, &scene, settings);
+            match result {
+                Some(result) => {
+                    println!("{label}: {result}");
+                    let _ = write!(csv, ",{result}");
+                }
+                None => {
+                    println!("{label}: -");
+                    csv.push(',');
+                }
+            }
+        }
+        csv.push('\n');
+    }
+
+    let path = cli.output.with_extension("sweep.csv");
+    match std::fs::write(&path, csv) {
+        Ok(()) => println!("Wrote {}", path.display()),
+        Err(e) => eprintln!("Could not write {}: {e}", path.display()),
+    }
+}
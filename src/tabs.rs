@@ -0,0 +1,241 @@
+use crate::scene::{LoadedScene, SceneCommand, SceneIo, StashedScenes};
+use crate::sim_control::SimClock;
+use crate::stats::SimStats;
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use std::path::PathBuf;
+
+/// A scene with its own particles, settings and statistics. Only the active
+/// tab lives in the world, the others are stashed.
+struct Tab {
+    id: u32,
+    name: String,
+    /// Statistics and clock of an inactive tab.
+    stats: SimStats,
+    steps: u64,
+    /// The scene file the tab was loaded from, which a reset goes back to.
+    loaded: Option<PathBuf>,
+}
+
+impl Tab {
+    fn new(id: u32, name: String) -> Self {
+        Self {
+            id,
+            name,
+            stats: default(),
+            steps: 0,
+            loaded: None,
+        }
+    }
+
+    /// Key of the tab in [`StashedScenes`].
+    fn key(&self) -> String {
+        format!("tab {}", self.id)
+    }
+}
+
+enum TabRequest {
+    /// Opens a tab with the default arena.
+    Open,
+    /// Opens a tab with a copy of the active scene.
+    Duplicate,
+    Switch(usize),
+    Close(usize),
+}
+
+#[derive(Resource)]
+pub struct SceneTabs {
+    tabs: Vec<Tab>,
+    active: usize,
+    next_id: u32,
+    /// Set by the tab bar, handled before the scene systems run.
+    request: Option<TabRequest>,
+    /// Statistics and clock to put back once a switched-to scene is loaded.
+    restore: Option<(SimStats, u64)>,
+}
+
+impl Default for SceneTabs {
+    fn default() -> Self {
+        Self {
+            tabs: vec![Tab::new(1, "Scene 1".to_string())],
+            active: 0,
+            next_id: 2,
+            request: None,
+            restore: None,
+        }
+    }
+}
+
+impl SceneTabs {
+    fn push(&mut self, name: String) {
+        self.tabs.push(Tab::new(self.next_id, name));
+        self.next_id += 1;
+        self.active = self.tabs.len() - 1;
+    }
+}
+
+/// Moves the live statistics and clock into the active tab and stashes its
+/// scene.
+fn park_active(
+    tabs: &mut SceneTabs,
+    stats: &mut SimStats,
+    clock: &SimClock,
+    loaded: &mut LoadedScene,
+    scene_commands: &mut EventWriter<SceneCommand>,
+) {
+    let tab = &mut tabs.tabs[tabs.active];
+    tab.stats = std::mem::take(stats);
+    tab.steps = clock.steps;
+    tab.loaded = loaded.0.take();
+    scene_commands.send(SceneCommand::Stash(tab.key()));
+}
+
+/// Makes tab `index` the active one and loads its stashed scene.
+fn activate(
+    tabs: &mut SceneTabs,
+    index: usize,
+    loaded: &mut LoadedScene,
+    scene_commands: &mut EventWriter<SceneCommand>,
+) {
+    let tab = &mut tabs.tabs[index];
+    loaded.0 = tab.loaded.take();
+    let restore = (std::mem::take(&mut tab.stats), tab.steps);
+    scene_commands.send(SceneCommand::Unstash(tab.key()));
+    tabs.restore = Some(restore);
+    tabs.active = index;
+}
+
+fn handle_tab_request(
+    mut tabs: ResMut<SceneTabs>,
+    mut stats: ResMut<SimStats>,
+    clock: Res<SimClock>,
+    mut loaded: ResMut<LoadedScene>,
+    mut stashed: ResMut<StashedScenes>,
+    mut scene_commands: EventWriter<SceneCommand>,
+) {
+    let Some(request) = tabs.request.take() else {
+        return;
+    };
+    match request {
+        TabRequest::Open => {
+            park_active(
+                &mut tabs,
+                &mut stats,
+                &clock,
+                &mut loaded,
+                &mut scene_commands,
+            );
+            let name = format!("Scene {}", tabs.next_id);
+            tabs.push(name);
+            scene_commands.send(SceneCommand::Reset);
+        }
+        TabRequest::Duplicate => {
+            // The world stays as it is and becomes the copy, while the
+            // original is stashed with a copy of the statistics.
+            let active = tabs.active;
+            let tab = &mut tabs.tabs[active];
+            tab.stats = stats.clone();
+            tab.steps = clock.steps;
+            tab.loaded = loaded.0.clone();
+            scene_commands.send(SceneCommand::Stash(tab.key()));
+            let name = format!("{} copy", tab.name);
+            tabs.push(name);
+        }
+        TabRequest::Switch(index) => {
+            if index == tabs.active || index >= tabs.tabs.len() {
+                return;
+            }
+            park_active(
+                &mut tabs,
+                &mut stats,
+                &clock,
+                &mut loaded,
+                &mut scene_commands,
+            );
+            activate(&mut tabs, index, &mut loaded, &mut scene_commands);
+        }
+        TabRequest::Close(index) => {
+            if tabs.tabs.len() < 2 || index >= tabs.tabs.len() {
+                return;
+            }
+            if index == tabs.active {
+                let next = if index > 0 { index - 1 } else { 1 };
+                activate(&mut tabs, next, &mut loaded, &mut scene_commands);
+            } else {
+                stashed.remove(&tabs.tabs[index].key());
+            }
+            tabs.tabs.remove(index);
+            if tabs.active > index {
+                tabs.active -= 1;
+            }
+        }
+    }
+}
+
+/// Puts back the statistics and clock of a tab after its scene is loaded,
+/// which resets them.
+fn restore_tab_state(
+    mut tabs: ResMut<SceneTabs>,
+    mut stats: ResMut<SimStats>,
+    mut clock: ResMut<SimClock>,
+) {
+    if let Some((restored, steps)) = tabs.restore.take() {
+        *stats = restored;
+        clock.steps = steps;
+    }
+}
+
+fn tabs_ui(mut egui_context: ResMut<EguiContext>, mut tabs: ResMut<SceneTabs>) {
+    let mut request = None;
+    let active = tabs.active;
+    let closable = tabs.tabs.len() > 1;
+    egui::Area::new("tabs")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 8.0))
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for (index, tab) in tabs.tabs.iter_mut().enumerate() {
+                    let response = ui.selectable_label(index == active, &tab.name);
+                    if response.clicked() {
+                        request = Some(TabRequest::Switch(index));
+                    }
+                    response.context_menu(|ui| {
+                        ui.text_edit_singleline(&mut tab.name);
+                        if ui
+                            .add_enabled(closable, egui::Button::new("Close"))
+                            .clicked()
+                        {
+                            request = Some(TabRequest::Close(index));
+                            ui.close_menu();
+                        }
+                    });
+                }
+                if ui.small_button("+").on_hover_text("New tab").clicked() {
+                    request = Some(TabRequest::Open);
+                }
+                if ui
+                    .small_button("Duplicate")
+                    .on_hover_text("Copy the active scene into a new tab")
+                    .clicked()
+                {
+                    request = Some(TabRequest::Duplicate);
+                }
+            });
+        });
+    if request.is_some() {
+        tabs.request = request;
+    }
+}
+
+/// Several independent scenes open at once, switched with a tab bar, to
+/// compare variations of a setup. Only the active tab is simulated.
+pub struct TabsPlugin;
+
+impl Plugin for TabsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneTabs>()
+            .add_system(handle_tab_request.label(Pipeline::Input))
+            .add_system(restore_tab_state.after(SceneIo).before(Pipeline::Spawning))
+            .add_system(tabs_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
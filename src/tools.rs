@@ -0,0 +1,84 @@
+use crate::input::{Action, ActionInput, Keybindings};
+use crate::tooltip::TooltipSettings;
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+
+/// What the left mouse button does in the arena.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tool {
+    #[default]
+    Spawn,
+    Measure,
+    Lattice,
+    Annotate,
+    /// Hold to grow a single particle, release to spawn it.
+    Charge,
+}
+
+impl Tool {
+    /// Every tool with its label and the action selecting it.
+    const ALL: [(Tool, &'static str, Action); 5] = [
+        (Tool::Spawn, "Spawn", Action::SpawnTool),
+        (Tool::Measure, "Measure", Action::MeasureTool),
+        (Tool::Lattice, "Lattice", Action::LatticeTool),
+        (Tool::Annotate, "Annotate", Action::AnnotateTool),
+        (Tool::Charge, "Charge", Action::ChargeTool),
+    ];
+}
+
+/// Whether the pointer is over an egui window, in which case tools ignore clicks.
+pub fn pointer_over_ui(egui_context: &mut EguiContext) -> bool {
+    egui_context.ctx_mut().is_pointer_over_area()
+}
+
+fn tool_hotkeys(actions: ActionInput, mut tool: ResMut<Tool>) {
+    for (candidate, _, action) in Tool::ALL {
+        if actions.just_pressed(action) && *tool != candidate {
+            *tool = candidate;
+        }
+    }
+
+    let step = if actions.just_pressed(Action::NextTool) {
+        1
+    } else if actions.just_pressed(Action::PreviousTool) {
+        Tool::ALL.len() - 1
+    } else {
+        return;
+    };
+    let index = Tool::ALL
+        .iter()
+        .position(|(candidate, ..)| candidate == &*tool)
+        .unwrap_or(0);
+    *tool = Tool::ALL[(index + step) % Tool::ALL.len()].0;
+}
+
+fn tools_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut tool: ResMut<Tool>,
+    mut tooltips: ResMut<TooltipSettings>,
+    bindings: Res<Keybindings>,
+) {
+    egui::Window::new("Tools").show(egui_context.ctx_mut(), |ui| {
+        let mut selected = *tool;
+        for (candidate, label, action) in Tool::ALL {
+            ui.radio_value(&mut selected, candidate, label)
+                .on_hover_text(bindings.label(action));
+        }
+        if selected != *tool {
+            *tool = selected;
+        }
+        ui.separator();
+        ui.checkbox(&mut tooltips.enabled, "Particle tooltips");
+    });
+}
+
+pub struct ToolsPlugin;
+
+impl Plugin for ToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tool>()
+            .add_system(tool_hotkeys.label(Pipeline::Input))
+            .add_system(tools_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
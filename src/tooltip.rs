@@ -0,0 +1,104 @@
+use crate::age::Age;
+use crate::camera::{cursor_world_position, MainCamera};
+use crate::selection::particle_at;
+use crate::spatial::SpatialHash;
+use crate::tools::pointer_over_ui;
+use crate::units::{format_duration, format_length, format_mass, format_speed, to_meters};
+use crate::{Particle, Pipeline};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct TooltipSettings {
+    pub enabled: bool,
+    /// Seconds the cursor has to rest on a particle before its tooltip shows.
+    pub delay: f32,
+}
+
+impl Default for TooltipSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            delay: 0.5,
+        }
+    }
+}
+
+/// Particle under the cursor and since when, in seconds since startup.
+#[derive(Resource, Default)]
+struct Hovered {
+    entity: Option<Entity>,
+    since: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn hover_particle(
+    settings: Res<TooltipSettings>,
+    time: Res<Time>,
+    windows: Res<Windows>,
+    spatial_hash: Res<SpatialHash>,
+    mut egui_context: ResMut<EguiContext>,
+    mut hovered: ResMut<Hovered>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    particles: Query<(&Transform, &Particle)>,
+) {
+    let entity = if settings.enabled && !pointer_over_ui(&mut egui_context) {
+        let window = windows.get_primary().unwrap();
+        let (camera, camera_transform) = camera_q.single();
+        cursor_world_position(window, camera, camera_transform)
+            .and_then(|cursor| particle_at(&spatial_hash, cursor, &particles))
+    } else {
+        None
+    };
+    if entity != hovered.entity {
+        hovered.entity = entity;
+        hovered.since = time.elapsed_seconds();
+    }
+}
+
+fn tooltip_ui(
+    settings: Res<TooltipSettings>,
+    time: Res<Time>,
+    hovered: Res<Hovered>,
+    mut egui_context: ResMut<EguiContext>,
+    particles: Query<(&Particle, &Velocity, &ReadMassProperties, &Age)>,
+) {
+    let Some(entity) = hovered.entity else {
+        return;
+    };
+    if time.elapsed_seconds() - hovered.since < settings.delay {
+        return;
+    }
+    let Ok((particle, velocity, mass_properties, age)) = particles.get(entity) else {
+        return;
+    };
+    egui::show_tooltip_at_pointer(
+        egui_context.ctx_mut(),
+        egui::Id::new("particle_tooltip"),
+        |ui| {
+            ui.label(format!("Size: {}", format_length(particle.size)));
+            ui.label(format!("Mass: {}", format_mass(mass_properties.0.mass)));
+            ui.label(format!(
+                "Speed: {}",
+                format_speed(to_meters(velocity.linvel.length()))
+            ));
+            ui.label(format!("Age: {}", format_duration(age.0)));
+        },
+    );
+}
+
+/// Shows details of the particle under the cursor after a short delay.
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TooltipSettings>()
+            .init_resource::<Hovered>()
+            .register_type::<TooltipSettings>()
+            .add_system(hover_particle.label(Pipeline::Input))
+            .add_system(tooltip_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
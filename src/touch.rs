@@ -0,0 +1,184 @@
+use crate::age::Age;
+use crate::camera::{CameraMode, CameraSettings, MainCamera};
+use crate::history::EditHistory;
+use crate::placement::Placement;
+use crate::selection::particle_at;
+use crate::spatial::SpatialHash;
+use crate::tools::{pointer_over_ui, Tool};
+use crate::units::{format_duration, format_length, format_speed, to_meters};
+use crate::{Particle, Particles, Pipeline, SpawnRequest};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use bevy_rapier2d::prelude::*;
+
+/// Seconds a finger has to rest before the touch counts as a long press.
+const LONG_PRESS: f32 = 0.5;
+/// How far a finger may move, in logical pixels, and still count as a tap.
+const TAP_SLOP: f32 = 10.0;
+
+/// Progress of the current single-finger touch.
+#[derive(Default)]
+struct TouchState {
+    held: f32,
+    /// Set once the touch can no longer become a tap or long press.
+    cancelled: bool,
+}
+
+/// Particle whose values are shown, after a long press or picked from its
+/// context menu.
+#[derive(Resource, Default)]
+pub struct Probe(pub Option<Entity>);
+
+/// Converts a touch position, measured from the top of the window, to the
+/// world position under it.
+fn touch_world_position(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    position: Vec2,
+) -> Option<Vec2> {
+    let cursor = Vec2::new(position.x, window.height() - position.y);
+    camera
+        .viewport_to_world(camera_transform, cursor)
+        .map(|ray| ray.origin.truncate())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn tap_and_long_press(
+    time: Res<Time>,
+    touches: Res<Touches>,
+    tool: Res<Tool>,
+    particles_per_tap: Res<Particles>,
+    placement: Res<Placement>,
+    windows: Res<Windows>,
+    spatial_hash: Res<SpatialHash>,
+    mut state: Local<TouchState>,
+    mut egui_context: ResMut<EguiContext>,
+    mut history: ResMut<EditHistory>,
+    mut probe: ResMut<Probe>,
+    mut spawn_requests: EventWriter<SpawnRequest>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    particles: Query<(&Transform, &Particle)>,
+) {
+    let pressed = touches.iter().count();
+    if touches.any_just_pressed() && pressed == 1 {
+        *state = TouchState {
+            held: 0.0,
+            cancelled: pointer_over_ui(&mut egui_context),
+        };
+    }
+    if pressed > 1 {
+        state.cancelled = true;
+    }
+    if state.cancelled {
+        return;
+    }
+
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let to_world = |position| touch_world_position(window, camera, camera_transform, position);
+
+    if let Some(touch) = touches.iter().next() {
+        state.held += time.delta_seconds();
+        if touch.distance().length() > TAP_SLOP {
+            state.cancelled = true;
+        } else if state.held >= LONG_PRESS {
+            state.cancelled = true;
+            if let Some(position) = to_world(touch.position()) {
+                probe.0 = particle_at(&spatial_hash, position, &particles);
+            }
+        }
+    } else if let Some(touch) = touches.iter_just_released().next() {
+        state.cancelled = true;
+        if *tool != Tool::Spawn {
+            return;
+        }
+        if let Some(position) = to_world(touch.position()) {
+            // Each tap is its own undo step.
+            history.close_stroke();
+            spawn_requests.send_batch(placement.requests(position, particles_per_tap.0));
+        }
+    }
+}
+
+fn pinch_and_pan(
+    mode: Res<CameraMode>,
+    settings: Res<CameraSettings>,
+    touches: Res<Touches>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    let mut pressed = touches.iter();
+    let (Some(a), Some(b), None) = (pressed.next(), pressed.next(), pressed.next()) else {
+        return;
+    };
+    let mut transform = camera_q.single_mut();
+
+    if matches!(*mode, CameraMode::Free | CameraMode::Follow) {
+        let previous = a.previous_position().distance(b.previous_position());
+        let current = a.position().distance(b.position());
+        if previous > 0.0 && current > 0.0 {
+            let scale = (transform.scale.x * previous / current)
+                .clamp(settings.min_zoom, settings.max_zoom);
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+    if *mode == CameraMode::Free {
+        let delta = (a.delta() + b.delta()) / 2.0;
+        let scale = transform.scale.x;
+        transform.translation.x -= delta.x * scale;
+        transform.translation.y += delta.y * scale;
+    }
+}
+
+fn probe_ui(
+    mut egui_context: ResMut<EguiContext>,
+    mut probe: ResMut<Probe>,
+    particles: Query<(&Transform, &Velocity, &Particle, &Age)>,
+) {
+    let Some(entity) = probe.0 else {
+        return;
+    };
+    let Ok((transform, velocity, particle, age)) = particles.get(entity) else {
+        probe.0 = None;
+        return;
+    };
+    let mut open = true;
+    egui::Window::new("Probe")
+        .open(&mut open)
+        .show(egui_context.ctx_mut(), |ui| {
+            let position = transform.translation.truncate();
+            ui.label(format!(
+                "Position: ({}, {})",
+                format_length(position.x),
+                format_length(position.y)
+            ));
+            ui.label(format!(
+                "Velocity: ({}, {})",
+                format_speed(to_meters(velocity.linvel.x)),
+                format_speed(to_meters(velocity.linvel.y))
+            ));
+            ui.label(format!(
+                "Speed: {}",
+                format_speed(to_meters(velocity.linvel.length()))
+            ));
+            ui.label(format!("Angular velocity: {:.2} rad/s", velocity.angvel));
+            ui.label(format!("Size: {}", format_length(particle.size)));
+            ui.label(format!("Age: {}", format_duration(age.0)));
+        });
+    if !open {
+        probe.0 = None;
+    }
+}
+
+/// Tap to spawn, long-press to probe a particle, pinch to zoom and drag
+/// with two fingers to pan.
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Probe>()
+            .add_system(tap_and_long_press.label(Pipeline::Input))
+            .add_system(pinch_and_pan.label(Pipeline::Input))
+            .add_system(probe_ui.label(Pipeline::Ui).after(Pipeline::Visual));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::camera::MainCamera;
+use crate::input::{Action, ActionInput};
+use crate::overlay::{overlay_painter, world_to_screen};
+use crate::quality::QualityLevel;
+use crate::Pipeline;
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Ring buffer of the most recent positions of a particle.
+#[derive(Component, Default)]
+pub struct Trail {
+    points: VecDeque<Vec2>,
+}
+
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct TrailSettings {
+    pub enabled: bool,
+    /// Number of past positions kept per particle.
+    pub length: usize,
+    pub width: f32,
+}
+
+impl Default for TrailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            length: 32,
+            width: 1.0,
+        }
+    }
+}
+
+fn toggle_trails(actions: ActionInput, mut settings: ResMut<TrailSettings>) {
+    if actions.just_pressed(Action::ToggleTrails) {
+        settings.enabled = !settings.enabled;
+    }
+}
+
+fn record_trails(
+    settings: Res<TrailSettings>,
+    quality: Res<QualityLevel>,
+    mut trails: Query<(&mut Trail, &Transform)>,
+) {
+    if !settings.enabled {
+        if settings.is_changed() {
+            for (mut trail, _) in trails.iter_mut() {
+                trail.points.clear();
+            }
+        }
+        return;
+    }
+    let length = quality.trail_length(settings.length);
+    for (mut trail, transform) in trails.iter_mut() {
+        trail.points.push_back(transform.translation.truncate());
+        while trail.points.len() > length {
+            trail.points.pop_front();
+        }
+    }
+}
+
+fn draw_trails(
+    settings: Res<TrailSettings>,
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    trails: Query<(&Trail, &Sprite)>,
+) {
+    if !settings.enabled {
+        return;
+    }
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_transform) = camera_q.single();
+    let painter = overlay_painter(egui_context.ctx_mut(), "trails", camera);
+    let to_screen = |point: Vec2| world_to_screen(camera, camera_transform, window, point);
+
+    for (trail, sprite) in trails.iter() {
+        let [r, g, b, _] = sprite.color.as_rgba_f32();
+        let len = trail.points.len();
+        for (i, (from, to)) in trail
+            .points
+            .iter()
+            .zip(trail.points.iter().skip(1))
+            .enumerate()
+        {
+            let (Some(from), Some(to)) = (to_screen(*from), to_screen(*to)) else {
+                continue;
+            };
+            let alpha = (i + 1) as f32 / len as f32;
+            let color = egui::Rgba::from_rgba_unmultiplied(r, g, b, alpha);
+            painter.line_segment([from, to], egui::Stroke::new(settings.width, color));
+        }
+    }
+}
+
+pub struct TrailsPlugin;
+
+impl Plugin for TrailsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrailSettings>()
+            .register_type::<TrailSettings>()
+            .add_system(toggle_trails.label(Pipeline::Input))
+            .add_system(
+                record_trails
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning)
+                    .after(toggle_trails),
+            )
+            .add_system(
+                draw_trails
+                    .label(Pipeline::Visual)
+                    .after(Pipeline::Spawning)
+                    .after(record_trails),
+            );
+    }
+}
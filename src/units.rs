@@ -0,0 +1,143 @@
+//! Conversion between SI lengths and world units, and formatting of physical
+//! values with a readable SI prefix, shared by every place that shows them, so
+//! the same quantity always reads the same.
+//!
+//! Transforms, sprites, colliders and velocities are in world units, which
+//! Rapier converts to meters with [`WORLD_UNITS_PER_METER`]. How many pixels a
+//! world unit covers is up to the camera zoom alone, so zooming never changes
+//! the physics.
+
+/// World units per meter, the physics scale Rapier is built with. Lengths
+/// given in SI go through [`from_meters`] and [`from_millimeters`], so the
+/// scene keeps its physical size if this changes.
+pub const WORLD_UNITS_PER_METER: f32 = 1000.0;
+
+/// World units of a length or speed given in meters.
+pub fn from_meters(meters: f32) -> f32 {
+    meters * WORLD_UNITS_PER_METER
+}
+
+pub fn from_millimeters(millimeters: f32) -> f32 {
+    from_meters(millimeters / 1000.0)
+}
+
+/// Meters of a length or speed given in world units.
+pub fn to_meters(world_units: f32) -> f32 {
+    world_units / WORLD_UNITS_PER_METER
+}
+
+/// Screen pixels covered by `world_units` at the camera `zoom`, the scale of
+/// its transform.
+pub fn to_pixels(world_units: f32, zoom: f32) -> f32 {
+    world_units / zoom
+}
+
+const PREFIXES: [(f32, &str); 7] = [
+    (1e9, "G"),
+    (1e6, "M"),
+    (1e3, "k"),
+    (1.0, ""),
+    (1e-3, "m"),
+    (1e-6, "µ"),
+    (1e-9, "n"),
+];
+
+/// Formats `value` in `unit` with the prefix that puts it between 1 and
+/// 1000, keeping three significant digits, e.g. `3.20 kJ`.
+pub fn format_si(value: f32, unit: &str) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return format!("{value} {unit}");
+    }
+    let mut index = PREFIXES
+        .iter()
+        .position(|(scale, _)| value.abs() >= *scale)
+        .unwrap_or(PREFIXES.len() - 1);
+    // Values that round up to 1000 read better with the next larger prefix.
+    if index > 0 && (value.abs() / PREFIXES[index].0).round() >= 1000.0 {
+        index -= 1;
+    }
+    let (scale, prefix) = PREFIXES[index];
+    let scaled = value / scale;
+    let rounds_below = |decimals: i32, limit: f32| {
+        let factor = 10f32.powi(decimals);
+        (scaled.abs() * factor).round() / factor < limit
+    };
+    let decimals = if rounds_below(2, 10.0) {
+        2
+    } else if rounds_below(1, 100.0) {
+        1
+    } else {
+        0
+    };
+    format!("{scaled:.decimals$} {prefix}{unit}")
+}
+
+/// Formats a length given in world units.
+pub fn format_length(world_units: f32) -> String {
+    format_si(to_meters(world_units), "m")
+}
+
+pub fn format_speed(meters_per_second: f32) -> String {
+    format_si(meters_per_second, "m/s")
+}
+
+pub fn format_mass(kilograms: f32) -> String {
+    format_si(kilograms * 1000.0, "g")
+}
+
+pub fn format_energy(joules: f32) -> String {
+    format_si(joules, "J")
+}
+
+/// Formats a duration, in plain seconds from one second up since nobody
+/// reads kiloseconds.
+pub fn format_duration(seconds: f32) -> String {
+    if seconds.abs() >= 1.0 {
+        format!("{seconds:.1} s")
+    } else {
+        format_si(seconds, "s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_zero_without_a_prefix() {
+        assert_eq!(format_si(0.0, "m"), "0 m");
+        assert_eq!(format_duration(0.0), "0 s");
+    }
+
+    #[test]
+    fn keeps_the_sign_of_negative_values() {
+        assert_eq!(format_si(-3200.0, "J"), "-3.20 kJ");
+        assert_eq!(format_si(-0.0125, "m"), "-12.5 mm");
+        assert_eq!(format_duration(-2.5), "-2.5 s");
+    }
+
+    #[test]
+    fn keeps_three_significant_digits() {
+        assert_eq!(format_si(1.0, "m"), "1.00 m");
+        assert_eq!(format_si(42.42, "m"), "42.4 m");
+        assert_eq!(format_si(123_456.0, "J"), "123 kJ");
+        assert_eq!(format_si(2.5e-7, "s"), "250 ns");
+    }
+
+    #[test]
+    fn rolls_over_to_the_next_prefix_when_rounding_up() {
+        assert_eq!(format_si(999.7, "m"), "1.00 km");
+        assert_eq!(format_si(0.99996, "m"), "1.00 m");
+        assert_eq!(format_si(-999_600.0, "J"), "-1.00 MJ");
+        assert_eq!(format_si(9.996, "m"), "10.0 m");
+        assert_eq!(format_si(99.96, "m"), "100 m");
+    }
+
+    #[test]
+    fn converts_between_si_and_world_units() {
+        assert_eq!(from_meters(1.0), WORLD_UNITS_PER_METER);
+        assert_eq!(from_millimeters(4.0), WORLD_UNITS_PER_METER * 0.004);
+        assert_eq!(to_meters(from_meters(0.25)), 0.25);
+        assert_eq!(format_length(from_millimeters(4.0)), "4.00 mm");
+    }
+}